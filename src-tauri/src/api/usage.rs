@@ -1,5 +1,7 @@
 //! Usage API client for fetching rate limits and credits
 
+use std::sync::Mutex;
+
 use anyhow::{Context, Result};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT};
 
@@ -10,11 +12,37 @@ use crate::types::{
 
 const CHATGPT_BACKEND_API: &str = "https://chatgpt.com/backend-api";
 
+/// Last successful usage fetch per account, kept for filtering and sorting
+static LATEST_USAGE: Mutex<Vec<UsageInfo>> = Mutex::new(Vec::new());
+
+fn remember_usage(usage: &UsageInfo) {
+    if usage.error.is_some() {
+        return;
+    }
+
+    let mut latest = LATEST_USAGE.lock().unwrap();
+    latest.retain(|cached| cached.account_id != usage.account_id);
+    latest.push(usage.clone());
+}
+
+/// Drop the cached usage of an account that was removed
+pub fn forget_usage(account_id: &str) {
+    LATEST_USAGE
+        .lock()
+        .unwrap()
+        .retain(|cached| cached.account_id != account_id);
+}
+
+/// The most recent successful usage of each account fetched since startup
+pub fn latest_usage() -> Vec<UsageInfo> {
+    LATEST_USAGE.lock().unwrap().clone()
+}
+
 /// Get usage information for an account
 pub async fn get_account_usage(account: &StoredAccount) -> Result<UsageInfo> {
     println!("[Usage] Fetching usage for account: {}", account.name);
 
-    match &account.auth_data {
+    let usage = match &account.auth_data {
         AuthData::ApiKey { .. } => {
             println!("[Usage] API key accounts don't support usage info");
             Ok(UsageInfo {
@@ -45,7 +73,12 @@ pub async fn get_account_usage(account: &StoredAccount) -> Result<UsageInfo> {
             )
             .await
         }
+    };
+
+    if let Ok(info) = &usage {
+        remember_usage(info);
     }
+    usage
 }
 
 /// Get usage with ChatGPT access token
@@ -0,0 +1,321 @@
+//! Activation history - when each account was the active one, and statistics derived from it
+
+use std::fs;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Utc};
+
+use crate::auth::storage::{get_activation_log_file, load_accounts, write_private_file};
+use crate::types::{
+    AccountActivityStats, AccountUsageStats, ActivationLog, ActivationRecord, StoredAccount,
+    WeeklyActiveTime,
+};
+
+/// Older records are dropped once the log grows past this size
+const MAX_ACTIVATION_ENTRIES: usize = 5000;
+pub const DEFAULT_STATS_WEEKS: u32 = 8;
+pub const DEFAULT_UNUSED_DAYS: u32 = 30;
+/// Ten years; longer ranges would only build huge, empty week lists
+pub const MAX_STATS_WEEKS: u32 = 520;
+pub const MAX_UNUSED_DAYS: u32 = 3650;
+
+/// Load the recorded activations
+pub fn load_activation_log() -> Result<ActivationLog> {
+    let path = get_activation_log_file()?;
+
+    if !path.exists() {
+        return Ok(ActivationLog::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read activation log: {}", path.display()))?;
+
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse activation log: {}", path.display()))
+}
+
+fn save_activation_log(log: &ActivationLog) -> Result<()> {
+    let path = get_activation_log_file()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(log).context("Failed to serialize activation log")?;
+
+    write_private_file(&path, content.as_bytes())
+        .with_context(|| format!("Failed to write activation log: {}", path.display()))
+}
+
+/// Close the open record and start a new one when the active account changed.
+///
+/// Returns whether the log was modified.
+pub(crate) fn apply_activation(
+    log: &mut ActivationLog,
+    active_account_id: Option<&str>,
+    now: DateTime<Utc>,
+) -> bool {
+    // Only the newest record can still be open
+    match log.entries.last_mut().filter(|r| r.ended_at.is_none()) {
+        Some(open) if Some(open.account_id.as_str()) == active_account_id => return false,
+        Some(open) => open.ended_at = Some(now),
+        None if active_account_id.is_none() => return false,
+        None => {}
+    }
+
+    if let Some(account_id) = active_account_id {
+        log.entries.push(ActivationRecord {
+            account_id: account_id.to_string(),
+            started_at: now,
+            ended_at: None,
+        });
+    }
+
+    if log.entries.len() > MAX_ACTIVATION_ENTRIES {
+        let overflow = log.entries.len() - MAX_ACTIVATION_ENTRIES;
+        log.entries.drain(..overflow);
+    }
+
+    true
+}
+
+/// Record the current active account; called whenever the accounts store is saved
+pub(crate) fn record_activation(active_account_id: Option<&str>) {
+    let result = load_activation_log().and_then(|mut log| {
+        if apply_activation(&mut log, active_account_id, Utc::now()) {
+            save_activation_log(&log)?;
+        }
+        Ok(())
+    });
+
+    if let Err(err) = result {
+        eprintln!("[Activity] Failed to record activation: {err:#}");
+    }
+}
+
+/// Midnight UTC on the Monday of the week containing `time`
+fn week_start(time: DateTime<Utc>) -> DateTime<Utc> {
+    let date = time.date_naive();
+    let monday = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+    monday.and_time(chrono::NaiveTime::MIN).and_utc()
+}
+
+/// Seconds of `record` that fall within `[start, end)`, counting an open record up to `now`
+fn overlap_secs(
+    record: &ActivationRecord,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> i64 {
+    let from = record.started_at.max(start);
+    let to = record.ended_at.unwrap_or(now).min(end).min(now);
+    (to - from).num_seconds().max(0)
+}
+
+pub(crate) fn compute_account_stats(
+    accounts: &[StoredAccount],
+    active_account_id: Option<&str>,
+    records: &[ActivationRecord],
+    now: DateTime<Utc>,
+    weeks: u32,
+    unused_days: u32,
+) -> AccountUsageStats {
+    let weeks = weeks.clamp(1, MAX_STATS_WEEKS);
+    let unused_days = unused_days.min(MAX_UNUSED_DAYS);
+    let since = week_start(now) - Duration::weeks(i64::from(weeks - 1));
+
+    let mut stats: Vec<AccountActivityStats> = accounts
+        .iter()
+        .map(|account| {
+            let account_records: Vec<&ActivationRecord> = records
+                .iter()
+                .filter(|record| record.account_id == account.id)
+                .collect();
+
+            let weekly: Vec<WeeklyActiveTime> = (0..weeks)
+                .map(|index| {
+                    let start = since + Duration::weeks(i64::from(index));
+                    let end = start + Duration::weeks(1);
+                    WeeklyActiveTime {
+                        week_start: start.date_naive(),
+                        active_secs: account_records
+                            .iter()
+                            .map(|record| overlap_secs(record, start, end, now))
+                            .sum(),
+                    }
+                })
+                .collect();
+
+            let is_active = active_account_id == Some(account.id.as_str());
+            let last_active_at = account_records
+                .iter()
+                .map(|record| record.ended_at.unwrap_or(now))
+                .chain(account.last_used_at)
+                .max();
+            let days_unused = if is_active {
+                0
+            } else {
+                (now - last_active_at.unwrap_or(account.created_at))
+                    .num_days()
+                    .max(0)
+            };
+
+            AccountActivityStats {
+                account_id: account.id.clone(),
+                account_name: account.name.clone(),
+                is_active,
+                active_secs: weekly.iter().map(|week| week.active_secs).sum(),
+                activation_count: account_records
+                    .iter()
+                    .filter(|record| record.started_at >= since)
+                    .count(),
+                weekly,
+                last_active_at,
+                days_unused,
+            }
+        })
+        .collect();
+    stats.sort_by_key(|account| std::cmp::Reverse(account.active_secs));
+
+    let mut unused: Vec<&AccountActivityStats> = stats
+        .iter()
+        .filter(|account| !account.is_active && account.days_unused >= i64::from(unused_days))
+        .collect();
+    unused.sort_by_key(|account| std::cmp::Reverse(account.days_unused));
+    let unused_account_ids = unused
+        .into_iter()
+        .map(|account| account.account_id.clone())
+        .collect();
+
+    // Activations of since-deleted accounts still count as switches
+    let switch_count = records
+        .iter()
+        .filter(|record| record.started_at >= since)
+        .count();
+
+    AccountUsageStats {
+        generated_at: now,
+        since,
+        weeks,
+        unused_days,
+        switch_count,
+        switches_per_week: switch_count as f64 / f64::from(weeks),
+        accounts: stats,
+        unused_account_ids,
+    }
+}
+
+/// Statistics over the last `weeks` weeks, flagging accounts unused for `unused_days`
+pub fn build_account_stats(weeks: u32, unused_days: u32) -> Result<AccountUsageStats> {
+    let store = load_accounts()?;
+    let log = load_activation_log()?;
+
+    Ok(compute_account_stats(
+        &store.accounts,
+        store.active_account_id.as_deref(),
+        &log.entries,
+        Utc::now(),
+        weeks,
+        unused_days,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_activation, compute_account_stats};
+    use crate::types::{ActivationLog, StoredAccount};
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn records_each_activation_with_start_and_end() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut log = ActivationLog::default();
+
+        assert!(apply_activation(&mut log, Some("a"), start));
+        assert!(!apply_activation(
+            &mut log,
+            Some("a"),
+            start + Duration::hours(1)
+        ));
+        assert!(apply_activation(
+            &mut log,
+            Some("b"),
+            start + Duration::hours(2)
+        ));
+        assert!(apply_activation(&mut log, None, start + Duration::hours(3)));
+        assert!(!apply_activation(
+            &mut log,
+            None,
+            start + Duration::hours(4)
+        ));
+
+        let spans: Vec<_> = log
+            .entries
+            .iter()
+            .map(|r| (r.account_id.as_str(), r.started_at, r.ended_at))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("a", start, Some(start + Duration::hours(2))),
+                (
+                    "b",
+                    start + Duration::hours(2),
+                    Some(start + Duration::hours(3))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_active_time_by_week_and_flags_unused_accounts() {
+        // Wednesday; the current week starts on Monday 2026-03-09
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        let mut work = StoredAccount::new_api_key("Work".to_string(), "sk-work".to_string());
+        let mut old = StoredAccount::new_api_key("Old".to_string(), "sk-old".to_string());
+        work.created_at = now - Duration::days(60);
+        old.created_at = now - Duration::days(90);
+        old.last_used_at = Some(now - Duration::days(45));
+
+        let mut log = ActivationLog::default();
+        // Sunday 22:00 to Monday 02:00 straddles the week boundary
+        apply_activation(
+            &mut log,
+            Some(&work.id),
+            Utc.with_ymd_and_hms(2026, 3, 8, 22, 0, 0).unwrap(),
+        );
+        apply_activation(
+            &mut log,
+            None,
+            Utc.with_ymd_and_hms(2026, 3, 9, 2, 0, 0).unwrap(),
+        );
+        apply_activation(&mut log, Some(&work.id), now - Duration::hours(1));
+
+        let stats = compute_account_stats(
+            &[work.clone(), old.clone()],
+            Some(&work.id),
+            &log.entries,
+            now,
+            2,
+            30,
+        );
+
+        let work_stats = &stats.accounts[0];
+        assert_eq!(work_stats.account_id, work.id);
+        let weekly: Vec<_> = work_stats.weekly.iter().map(|w| w.active_secs).collect();
+        assert_eq!(weekly, [2 * 3600, 3 * 3600]);
+        assert_eq!(work_stats.active_secs, 5 * 3600);
+        assert_eq!(work_stats.activation_count, 2);
+        assert_eq!(stats.switch_count, 2);
+        assert_eq!(stats.switches_per_week, 1.0);
+
+        assert_eq!(stats.accounts[1].days_unused, 45);
+        assert_eq!(stats.unused_account_ids, [old.id]);
+
+        let clamped = compute_account_stats(&[work], None, &log.entries, now, u32::MAX, u32::MAX);
+        assert_eq!(clamped.weeks, 520);
+        assert_eq!(clamped.accounts[0].weekly.len(), 520);
+        assert_eq!(clamped.unused_days, 3650);
+    }
+}
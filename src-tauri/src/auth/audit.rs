@@ -0,0 +1,194 @@
+//! Audit log - an append-only record of account operations in audit-log.jsonl
+//!
+//! Entries hold account IDs, names and short details only; credentials never
+//! reach this file.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+
+use crate::auth::storage::{get_audit_log_file, write_private_file};
+use crate::types::{AuditAction, AuditEntry, AuditLogQuery, StoredAccount};
+
+/// Name of the OS user running the app
+fn current_actor() -> Option<String> {
+    ["USER", "USERNAME"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+}
+
+/// Append one entry as a JSON line, creating the file owner-only on Unix
+pub(crate) fn append_audit_entry(path: &Path, entry: &AuditEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let mut line = serde_json::to_string(entry).context("Failed to serialize audit entry")?;
+    line.push('\n');
+
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to open audit log: {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to write audit log: {}", path.display()))
+}
+
+/// Record an operation in the audit log.
+///
+/// A failed write is reported but does not fail the operation being audited.
+pub(crate) fn record_audit(
+    action: AuditAction,
+    account: Option<&StoredAccount>,
+    details: Option<String>,
+) {
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        actor: current_actor(),
+        action,
+        account_id: account.map(|a| a.id.clone()),
+        account_name: account.map(|a| a.name.clone()),
+        details,
+    };
+
+    if let Err(err) = get_audit_log_file().and_then(|path| append_audit_entry(&path, &entry)) {
+        eprintln!("[Audit] Failed to record {action:?}: {err:#}");
+    }
+}
+
+/// Read every entry from an audit log file, oldest first
+pub(crate) fn read_audit_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read audit log: {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                // A torn last line from a crash should not hide the rest of the log
+                eprintln!("[Audit] Skipping unreadable line {}: {err}", index + 1);
+                None
+            }
+        })
+        .collect())
+}
+
+pub(crate) fn filter_audit_entries(
+    entries: Vec<AuditEntry>,
+    query: &AuditLogQuery,
+) -> Vec<AuditEntry> {
+    entries
+        .into_iter()
+        .filter(|entry| query.since.is_none_or(|since| entry.timestamp >= since))
+        .filter(|entry| query.until.is_none_or(|until| entry.timestamp < until))
+        .filter(|entry| {
+            query
+                .account_id
+                .as_deref()
+                .is_none_or(|account_id| entry.account_id.as_deref() == Some(account_id))
+        })
+        .collect()
+}
+
+/// Audit entries matching the query, oldest first
+pub fn query_audit_log(query: &AuditLogQuery) -> Result<Vec<AuditEntry>> {
+    let entries = read_audit_entries(&get_audit_log_file()?)?;
+    Ok(filter_audit_entries(entries, query))
+}
+
+/// Write the entries matching the query to a JSON file; returns how many were exported
+pub fn export_audit_log(query: &AuditLogQuery, path: &str) -> Result<usize> {
+    let entries = query_audit_log(query)?;
+    let content =
+        serde_json::to_string_pretty(&entries).context("Failed to serialize audit log")?;
+
+    write_private_file(Path::new(path), content.as_bytes())
+        .with_context(|| format!("Failed to write audit export: {path}"))?;
+
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_audit_entry, filter_audit_entries, read_audit_entries};
+    use crate::types::{AuditAction, AuditEntry, AuditLogQuery};
+    use chrono::{Duration, TimeZone, Utc};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn entry(account_id: &str, hours: i64) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap() + Duration::hours(hours),
+            actor: Some("tester".to_string()),
+            action: AuditAction::AccountSwitched,
+            account_id: Some(account_id.to_string()),
+            account_name: Some(account_id.to_uppercase()),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn appends_lines_and_skips_torn_ones() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock drift")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("codex-switcher-audit-tests-{suffix}"));
+        let path = dir.join("audit-log.jsonl");
+
+        append_audit_entry(&path, &entry("a", 0)).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| std::io::Write::write_all(&mut file, b"{\"timestamp\":\n"))
+            .unwrap();
+        append_audit_entry(&path, &entry("b", 1)).unwrap();
+
+        let entries = read_audit_entries(&path).unwrap();
+        let ids: Vec<_> = entries
+            .iter()
+            .filter_map(|e| e.account_id.clone())
+            .collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn filters_by_time_range_and_account() {
+        let entries = vec![entry("a", 0), entry("b", 1), entry("a", 2), entry("a", 3)];
+        let query = AuditLogQuery {
+            since: Some(entries[1].timestamp),
+            until: Some(entries[3].timestamp),
+            account_id: Some("a".to_string()),
+        };
+
+        let matched = filter_audit_entries(entries.clone(), &query);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].timestamp, entries[2].timestamp);
+
+        assert_eq!(
+            filter_audit_entries(entries, &AuditLogQuery::default()).len(),
+            4
+        );
+    }
+}
@@ -0,0 +1,486 @@
+//! Encrypted account bundles - move stored accounts between machines
+
+use std::fs;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::audit::record_audit;
+use crate::auth::identity::{find_duplicate_account, merge_credentials_into};
+use crate::auth::importer::unique_account_name;
+use crate::auth::storage::{load_accounts, save_accounts, write_private_file};
+use crate::types::{
+    AccountsStore, AuditAction, BundleExportResult, BundleImportAction, BundleImportEntry,
+    BundleImportPlan, DuplicateStrategy, StoredAccount,
+};
+
+const BUNDLE_FORMAT: &str = "codex-switcher-bundle";
+const BUNDLE_VERSION: u32 = 2;
+const KDF_ALGORITHM: &str = "argon2id";
+const CIPHER: &str = "aes-256-gcm";
+const MIN_PASSPHRASE_LEN: usize = 8;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// Upper bounds for key parameters read from a bundle, so a crafted file cannot
+/// make opening it take unbounded memory or time
+const MAX_KDF_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 10;
+const MAX_KDF_PARALLELISM: u32 = 16;
+
+/// Argon2id cost parameters, stored in the bundle so future builds can change defaults
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl KdfParams {
+    fn check_limits(self) -> Result<()> {
+        if self.memory_kib > MAX_KDF_MEMORY_KIB
+            || self.iterations > MAX_KDF_ITERATIONS
+            || self.parallelism > MAX_KDF_PARALLELISM
+        {
+            anyhow::bail!(
+                "Bundle key parameters exceed the supported limits ({} KiB, {} iterations, {} lanes)",
+                self.memory_kib,
+                self.iterations,
+                self.parallelism
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// On-disk bundle envelope; only format and key/cipher parameters are stored in clear
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundleEnvelope {
+    format: String,
+    version: u32,
+    kdf: String,
+    #[serde(flatten)]
+    kdf_params: KdfParams,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Decrypted bundle content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundlePayload {
+    exported_at: DateTime<Utc>,
+    accounts: Vec<StoredAccount>,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<[u8; 32]> {
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|e| anyhow::anyhow!("Invalid bundle key parameters: {e}"))?;
+
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive bundle key: {e}"))?;
+    Ok(key)
+}
+
+/// Authenticate every clear header field except the nonce, which GCM already covers,
+/// so editing the header makes decryption fail
+fn associated_data(envelope: &BundleEnvelope) -> Vec<u8> {
+    let params = envelope.kdf_params;
+    format!(
+        "{}:{}:{}:{}:{}:{}:{}:{}",
+        envelope.format,
+        envelope.version,
+        envelope.kdf,
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        envelope.salt,
+        envelope.cipher
+    )
+    .into_bytes()
+}
+
+fn seal_payload(
+    payload: &BundlePayload,
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<BundleEnvelope> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        anyhow::bail!("Bundle passphrase must be at least {MIN_PASSPHRASE_LEN} characters");
+    }
+
+    let plaintext = serde_json::to_vec(payload).context("Failed to serialize bundle")?;
+    let salt = random_bytes::<SALT_LEN>();
+    let nonce = random_bytes::<NONCE_LEN>();
+    let key = derive_key(passphrase, &salt, params)?;
+
+    let mut envelope = BundleEnvelope {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        kdf: KDF_ALGORITHM.to_string(),
+        kdf_params: params,
+        salt: b64().encode(salt),
+        cipher: CIPHER.to_string(),
+        nonce: b64().encode(nonce),
+        ciphertext: String::new(),
+    };
+
+    let cipher = Aes256Gcm::new_from_slice(&key).context("Invalid bundle key")?;
+    let aad = associated_data(&envelope);
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt bundle"))?;
+
+    envelope.ciphertext = b64().encode(ciphertext);
+    Ok(envelope)
+}
+
+fn open_envelope(envelope: &BundleEnvelope, passphrase: &str) -> Result<BundlePayload> {
+    if envelope.format != BUNDLE_FORMAT {
+        anyhow::bail!("Not a Codex Switcher account bundle");
+    }
+    if envelope.version != BUNDLE_VERSION {
+        anyhow::bail!("Unsupported bundle version: {}", envelope.version);
+    }
+    if envelope.kdf != KDF_ALGORITHM || envelope.cipher != CIPHER {
+        anyhow::bail!(
+            "Unsupported bundle encryption: {} / {}",
+            envelope.kdf,
+            envelope.cipher
+        );
+    }
+
+    let salt = b64()
+        .decode(&envelope.salt)
+        .context("Bundle salt is corrupted")?;
+    let nonce = b64()
+        .decode(&envelope.nonce)
+        .context("Bundle nonce is corrupted")?;
+    let ciphertext = b64()
+        .decode(&envelope.ciphertext)
+        .context("Bundle content is corrupted")?;
+    if nonce.len() != NONCE_LEN {
+        anyhow::bail!("Bundle nonce is corrupted");
+    }
+    envelope.kdf_params.check_limits()?;
+
+    let key = derive_key(passphrase, &salt, envelope.kdf_params)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).context("Invalid bundle key")?;
+    let aad = associated_data(envelope);
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted bundle"))?;
+
+    serde_json::from_slice(&plaintext).context("Bundle content is not a valid account list")
+}
+
+/// Export accounts to a passphrase-encrypted bundle file.
+///
+/// With no `account_ids`, every stored account is exported.
+pub fn export_accounts_bundle(
+    path: &str,
+    passphrase: &str,
+    account_ids: Option<&[String]>,
+) -> Result<BundleExportResult> {
+    let store = load_accounts()?;
+
+    let accounts: Vec<StoredAccount> = match account_ids {
+        Some(ids) => {
+            if let Some(missing) = ids
+                .iter()
+                .find(|id| !store.accounts.iter().any(|a| a.id == **id))
+            {
+                anyhow::bail!("Account not found: {missing}");
+            }
+            store
+                .accounts
+                .into_iter()
+                .filter(|a| ids.contains(&a.id))
+                .collect()
+        }
+        None => store.accounts,
+    };
+
+    if accounts.is_empty() {
+        anyhow::bail!("No accounts to export");
+    }
+
+    let account_count = accounts.len();
+    let payload = BundlePayload {
+        exported_at: Utc::now(),
+        accounts,
+    };
+    let envelope = seal_payload(&payload, passphrase, KdfParams::default())?;
+    let content = serde_json::to_string_pretty(&envelope).context("Failed to serialize bundle")?;
+
+    write_private_file(std::path::Path::new(path), content.as_bytes())
+        .with_context(|| format!("Failed to write bundle file: {path}"))?;
+
+    println!("[Bundle] Exported {account_count} account(s)");
+
+    Ok(BundleExportResult {
+        path: path.to_string(),
+        account_count,
+    })
+}
+
+fn read_bundle(path: &str, passphrase: &str) -> Result<BundlePayload> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read bundle file: {path}"))?;
+    let envelope: BundleEnvelope = serde_json::from_str(&content)
+        .with_context(|| format!("Not a Codex Switcher account bundle: {path}"))?;
+
+    open_envelope(&envelope, passphrase)
+}
+
+/// Decide what happens to each bundled account, applying the changes to `store`
+fn plan_bundle_import(
+    store: &mut AccountsStore,
+    payload: BundlePayload,
+    on_duplicate: DuplicateStrategy,
+) -> BundleImportPlan {
+    let mut entries = Vec::with_capacity(payload.accounts.len());
+
+    for mut incoming in payload.accounts {
+        let existing_index = find_duplicate_account(&store.accounts, &incoming)
+            .and_then(|existing| store.accounts.iter().position(|a| a.id == existing.id));
+
+        let (action, existing) = match (existing_index, on_duplicate) {
+            (Some(index), DuplicateStrategy::Merge) => {
+                merge_credentials_into(&mut store.accounts[index], &incoming);
+                (BundleImportAction::Update, Some(&store.accounts[index]))
+            }
+            (Some(index), DuplicateStrategy::Skip) => {
+                (BundleImportAction::Skip, Some(&store.accounts[index]))
+            }
+            (None, _) => {
+                if store.accounts.iter().any(|a| a.id == incoming.id) {
+                    incoming.id = Uuid::new_v4().to_string();
+                }
+                incoming.name = unique_account_name(incoming.name.clone(), &store.accounts);
+                (BundleImportAction::Add, None)
+            }
+        };
+
+        entries.push(BundleImportEntry {
+            name: incoming.name.clone(),
+            email: incoming.email.clone(),
+            plan_type: incoming.plan_type.clone(),
+            auth_mode: incoming.auth_mode,
+            action,
+            existing_account_id: existing.map(|a| a.id.clone()),
+            existing_account_name: existing.map(|a| a.name.clone()),
+        });
+
+        if action == BundleImportAction::Add {
+            store.accounts.push(incoming);
+        }
+    }
+
+    BundleImportPlan {
+        exported_at: payload.exported_at,
+        applied: false,
+        entries,
+    }
+}
+
+/// Decrypt a bundle and show what importing it would add, update or skip
+pub fn preview_accounts_bundle(
+    path: &str,
+    passphrase: &str,
+    on_duplicate: DuplicateStrategy,
+) -> Result<BundleImportPlan> {
+    let payload = read_bundle(path, passphrase)?;
+    let mut store = load_accounts()?;
+    Ok(plan_bundle_import(&mut store, payload, on_duplicate))
+}
+
+/// Decrypt a bundle and merge it into the local store
+pub fn import_accounts_bundle(
+    path: &str,
+    passphrase: &str,
+    on_duplicate: DuplicateStrategy,
+) -> Result<BundleImportPlan> {
+    let payload = read_bundle(path, passphrase)?;
+    let mut store = load_accounts()?;
+    let mut plan = plan_bundle_import(&mut store, payload, on_duplicate);
+
+    if store.active_account_id.is_none() {
+        store.active_account_id = store.accounts.first().map(|a| a.id.clone());
+    }
+    save_accounts(&store)?;
+    plan.applied = true;
+
+    for entry in &plan.entries {
+        let (action, account_id) = match entry.action {
+            BundleImportAction::Add => (AuditAction::AccountAdded, None),
+            BundleImportAction::Update => (
+                AuditAction::CredentialsUpdated,
+                entry.existing_account_id.as_deref(),
+            ),
+            BundleImportAction::Skip => continue,
+        };
+        let account = store.accounts.iter().find(|a| match account_id {
+            Some(account_id) => a.id == account_id,
+            None => a.name == entry.name,
+        });
+        record_audit(action, account, Some("Imported from bundle".to_string()));
+    }
+
+    println!(
+        "[Bundle] Imported bundle with {} account(s)",
+        plan.entries.len()
+    );
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_envelope, plan_bundle_import, seal_payload, BundlePayload, KdfParams};
+    use crate::types::{AccountsStore, BundleImportAction, DuplicateStrategy, StoredAccount};
+    use chrono::Utc;
+
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn chatgpt(name: &str, account_id: &str, refresh_token: &str) -> StoredAccount {
+        StoredAccount::new_chatgpt(
+            name.to_string(),
+            Some("me@example.com".to_string()),
+            Some("pro".to_string()),
+            "id-token".to_string(),
+            "access-token".to_string(),
+            refresh_token.to_string(),
+            Some(account_id.to_string()),
+        )
+    }
+
+    #[test]
+    fn round_trips_with_the_right_passphrase_only() {
+        let payload = BundlePayload {
+            exported_at: Utc::now(),
+            accounts: vec![chatgpt("Work", "acct-1", "refresh-1")],
+        };
+
+        let envelope = seal_payload(&payload, "correct horse", TEST_PARAMS).expect("seal");
+        assert!(!envelope.ciphertext.contains("refresh-1"));
+
+        let opened = open_envelope(&envelope, "correct horse").expect("open");
+        assert_eq!(opened.accounts.len(), 1);
+        assert_eq!(opened.accounts[0].name, "Work");
+
+        let err = open_envelope(&envelope, "wrong horse").expect_err("wrong passphrase");
+        assert!(err.to_string().contains("Wrong passphrase"));
+    }
+
+    #[test]
+    fn rejects_edited_or_oversized_key_parameters() {
+        let payload = BundlePayload {
+            exported_at: Utc::now(),
+            accounts: Vec::new(),
+        };
+        let envelope = seal_payload(&payload, "correct horse", TEST_PARAMS).expect("seal");
+
+        let mut edited = envelope.clone();
+        edited.kdf_params.iterations = 2;
+        let err = open_envelope(&edited, "correct horse").expect_err("edited header");
+        assert!(err.to_string().contains("corrupted bundle"));
+
+        let mut older = envelope.clone();
+        older.version = 1;
+        let err = open_envelope(&older, "correct horse").expect_err("unsupported version");
+        assert!(err.to_string().contains("Unsupported bundle version"));
+
+        let mut oversized = envelope;
+        oversized.kdf_params.memory_kib = u32::MAX;
+        let err = open_envelope(&oversized, "correct horse").expect_err("oversized params");
+        assert!(err.to_string().contains("exceed the supported limits"));
+    }
+
+    #[test]
+    fn rejects_short_passphrases() {
+        let payload = BundlePayload {
+            exported_at: Utc::now(),
+            accounts: Vec::new(),
+        };
+
+        assert!(seal_payload(&payload, "short", TEST_PARAMS).is_err());
+    }
+
+    #[test]
+    fn plans_update_for_known_logins_and_add_for_new_ones() {
+        let mut store = AccountsStore::default();
+        store
+            .accounts
+            .push(chatgpt("Work", "acct-1", "refresh-old"));
+
+        let payload = BundlePayload {
+            exported_at: Utc::now(),
+            accounts: vec![
+                chatgpt("Work laptop", "acct-1", "refresh-new"),
+                chatgpt("Work", "acct-2", "refresh-2"),
+            ],
+        };
+
+        let plan = plan_bundle_import(&mut store, payload, DuplicateStrategy::Merge);
+
+        assert_eq!(plan.entries[0].action, BundleImportAction::Update);
+        assert_eq!(
+            plan.entries[0].existing_account_name.as_deref(),
+            Some("Work")
+        );
+        assert_eq!(plan.entries[1].action, BundleImportAction::Add);
+        assert_eq!(plan.entries[1].name, "Work 2");
+        assert_eq!(store.accounts.len(), 2);
+    }
+}
@@ -0,0 +1,361 @@
+//! JWT claims - typed, unverified decoding of ChatGPT id and access tokens
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+use crate::types::TokenOrganization;
+
+/// URL-safe base64 that accepts segments with or without `=` padding
+const JWT_SEGMENT_ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Claims OpenAI nests under `https://api.openai.com/auth`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OpenAiAuthClaims {
+    #[serde(deserialize_with = "lenient")]
+    pub chatgpt_plan_type: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub chatgpt_account_id: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub chatgpt_user_id: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub user_id: Option<String>,
+    #[serde(deserialize_with = "lenient_list")]
+    pub organizations: Vec<OrganizationClaim>,
+}
+
+/// One entry of the `organizations` claim
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OrganizationClaim {
+    #[serde(deserialize_with = "lenient")]
+    pub id: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub title: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub role: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub is_default: bool,
+}
+
+/// Profile claims OpenAI nests under `https://api.openai.com/profile` in access tokens
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OpenAiProfileClaims {
+    #[serde(deserialize_with = "lenient")]
+    pub email: Option<String>,
+}
+
+/// Claims of a ChatGPT id token
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IdTokenClaims {
+    #[serde(deserialize_with = "lenient")]
+    pub sub: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub email: Option<String>,
+    #[serde(deserialize_with = "deserialize_numeric_date")]
+    pub iat: Option<i64>,
+    #[serde(deserialize_with = "deserialize_numeric_date")]
+    pub exp: Option<i64>,
+    #[serde(rename = "https://api.openai.com/auth", deserialize_with = "lenient")]
+    pub auth: OpenAiAuthClaims,
+}
+
+/// Claims of a ChatGPT access token
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AccessTokenClaims {
+    #[serde(deserialize_with = "lenient")]
+    pub sub: Option<String>,
+    #[serde(deserialize_with = "deserialize_numeric_date")]
+    pub iat: Option<i64>,
+    #[serde(deserialize_with = "deserialize_numeric_date")]
+    pub exp: Option<i64>,
+    #[serde(rename = "https://api.openai.com/auth", deserialize_with = "lenient")]
+    pub auth: OpenAiAuthClaims,
+    #[serde(
+        rename = "https://api.openai.com/profile",
+        deserialize_with = "lenient"
+    )]
+    pub profile: OpenAiProfileClaims,
+}
+
+/// Fall back to the default when a claim has an unexpected shape, so one odd
+/// claim does not hide the rest of the token
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+/// Like `lenient`, but drops only the list entries that do not parse
+fn lenient_list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let Ok(values) = serde_json::from_value::<Vec<serde_json::Value>>(
+        serde_json::Value::deserialize(deserializer)?,
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
+}
+
+/// JWT NumericDate values are seconds, sometimes written with a fraction
+fn deserialize_numeric_date<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(value
+        .as_i64()
+        .or_else(|| value.as_f64().map(|seconds| seconds as i64)))
+}
+
+fn timestamp(seconds: Option<i64>) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds?, 0)
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.clone().filter(|value| !value.trim().is_empty())
+}
+
+impl OpenAiAuthClaims {
+    /// Organizations with an ID, in token order
+    pub fn organizations(&self) -> Vec<TokenOrganization> {
+        self.organizations
+            .iter()
+            .filter_map(|organization| {
+                Some(TokenOrganization {
+                    id: non_empty(&organization.id)?,
+                    title: organization.title.clone(),
+                    role: organization.role.clone(),
+                    is_default: organization.is_default,
+                })
+            })
+            .collect()
+    }
+}
+
+impl IdTokenClaims {
+    pub fn email(&self) -> Option<String> {
+        non_empty(&self.email)
+    }
+
+    pub fn plan_type(&self) -> Option<String> {
+        non_empty(&self.auth.chatgpt_plan_type)
+    }
+
+    pub fn account_id(&self) -> Option<String> {
+        non_empty(&self.auth.chatgpt_account_id)
+    }
+
+    /// ChatGPT user ID, falling back to the older `user_id` claim
+    pub fn user_id(&self) -> Option<String> {
+        non_empty(&self.auth.chatgpt_user_id).or_else(|| non_empty(&self.auth.user_id))
+    }
+
+    pub fn organizations(&self) -> Vec<TokenOrganization> {
+        self.auth.organizations()
+    }
+
+    pub fn issued_at(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.iat)
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.exp)
+    }
+}
+
+impl AccessTokenClaims {
+    pub fn plan_type(&self) -> Option<String> {
+        non_empty(&self.auth.chatgpt_plan_type)
+    }
+
+    pub fn issued_at(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.iat)
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.exp)
+    }
+}
+
+/// Decode the payload of a JWT without verifying its signature
+pub fn decode_jwt_claims<T: DeserializeOwned>(token: &str) -> Option<T> {
+    let mut parts = token.trim().split('.');
+    let (Some(_header), Some(payload), Some(_signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+
+    let payload = JWT_SEGMENT_ENGINE.decode(payload).ok()?;
+    serde_json::from_slice(&payload).ok()
+}
+
+/// Parse the claims of an id token; `None` when it is not a decodable JWT
+pub fn parse_id_token(id_token: &str) -> Option<IdTokenClaims> {
+    decode_jwt_claims(id_token)
+}
+
+/// Parse the claims of an access token; `None` when it is not a decodable JWT
+pub fn parse_access_token(access_token: &str) -> Option<AccessTokenClaims> {
+    decode_jwt_claims(access_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_access_token, parse_id_token};
+    use base64::Engine;
+
+    const PERSONAL_ID_TOKEN: &str = include_str!("fixtures/id_token_personal.json");
+    const TEAM_ID_TOKEN: &str = include_str!("fixtures/id_token_team.json");
+    const LEGACY_ID_TOKEN: &str = include_str!("fixtures/id_token_legacy.json");
+    const ACCESS_TOKEN: &str = include_str!("fixtures/access_token.json");
+
+    fn jwt(payload: &str, padded: bool) -> String {
+        let engine = if padded {
+            base64::engine::general_purpose::URL_SAFE
+        } else {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+        };
+        let compact: serde_json::Value = serde_json::from_str(payload).expect("fixture json");
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            engine.encode(br#"{"alg":"RS256","typ":"JWT"}"#),
+            engine.encode(compact.to_string())
+        )
+    }
+
+    #[test]
+    fn parses_personal_plus_id_token() {
+        let claims = parse_id_token(&jwt(PERSONAL_ID_TOKEN, false)).expect("claims");
+
+        assert_eq!(claims.email().as_deref(), Some("dev@example.com"));
+        assert_eq!(claims.plan_type().as_deref(), Some("plus"));
+        assert_eq!(
+            claims.account_id().as_deref(),
+            Some("2f0c6b8e-6f4c-4bd3-9a0e-2a1f3c4d5e6f")
+        );
+        assert_eq!(claims.user_id().as_deref(), Some("user-Abc123Def456"));
+        assert_eq!(claims.organizations().len(), 1);
+        assert_eq!(
+            claims.expires_at().map(|time| time.timestamp()),
+            Some(1_735_693_200)
+        );
+    }
+
+    #[test]
+    fn parses_team_id_token_with_several_organizations() {
+        let claims = parse_id_token(&jwt(TEAM_ID_TOKEN, true)).expect("padded claims");
+
+        let organizations = claims.organizations();
+        assert_eq!(claims.plan_type().as_deref(), Some("team"));
+        assert_eq!(organizations.len(), 2);
+        assert_eq!(organizations[0].id, "org-personal123");
+        assert!(organizations[0].is_default);
+        assert_eq!(organizations[1].title.as_deref(), Some("Acme Engineering"));
+        assert_eq!(organizations[1].role.as_deref(), Some("reader"));
+    }
+
+    #[test]
+    fn parses_legacy_id_token_without_openai_claims() {
+        let claims = parse_id_token(&jwt(LEGACY_ID_TOKEN, false)).expect("claims");
+
+        assert_eq!(claims.email().as_deref(), Some("legacy@example.com"));
+        assert_eq!(claims.plan_type(), None);
+        assert_eq!(claims.account_id(), None);
+        assert_eq!(claims.user_id().as_deref(), Some("user-legacy"));
+        // Fractional NumericDate
+        assert_eq!(
+            claims.issued_at().map(|time| time.timestamp()),
+            Some(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn parses_access_token_expiry_and_plan() {
+        let claims = parse_access_token(&jwt(ACCESS_TOKEN, false)).expect("claims");
+
+        assert_eq!(claims.plan_type().as_deref(), Some("pro"));
+        assert_eq!(claims.profile.email.as_deref(), Some("dev@example.com"));
+        assert_eq!(
+            claims.expires_at().map(|time| time.timestamp()),
+            Some(1_736_553_600)
+        );
+    }
+
+    #[test]
+    fn keeps_other_claims_when_one_is_malformed() {
+        let payload = r#"{
+            "sub": ["not", "a", "string"],
+            "email": "dev@example.com",
+            "iat": "yesterday",
+            "exp": 1735693200,
+            "https://api.openai.com/auth": {
+                "chatgpt_plan_type": "plus",
+                "chatgpt_account_id": "acct-1",
+                "user_id": 42,
+                "organizations": [
+                    { "id": "org-1", "title": "Personal", "is_default": "yes" },
+                    "org-2",
+                    { "id": "org-3", "title": 7 }
+                ]
+            }
+        }"#;
+        let claims = parse_id_token(&jwt(payload, false)).expect("claims");
+
+        assert_eq!(claims.sub, None);
+        assert_eq!(claims.email().as_deref(), Some("dev@example.com"));
+        assert_eq!(claims.plan_type().as_deref(), Some("plus"));
+        assert_eq!(claims.account_id().as_deref(), Some("acct-1"));
+        assert_eq!(claims.user_id(), None);
+        assert_eq!(claims.issued_at(), None);
+        assert_eq!(
+            claims.expires_at().map(|time| time.timestamp()),
+            Some(1_735_693_200)
+        );
+        let ids: Vec<_> = claims
+            .organizations()
+            .into_iter()
+            .map(|organization| organization.id)
+            .collect();
+        assert_eq!(ids, ["org-1", "org-3"]);
+
+        // A malformed namespace object only loses the OpenAI claims
+        let claims = parse_id_token(&jwt(
+            r#"{ "email": "dev@example.com", "https://api.openai.com/auth": "oops" }"#,
+            false,
+        ))
+        .expect("claims");
+        assert_eq!(claims.email().as_deref(), Some("dev@example.com"));
+        assert_eq!(claims.plan_type(), None);
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_jwts() {
+        assert!(parse_id_token("").is_none());
+        assert!(parse_id_token("not-a-jwt").is_none());
+        assert!(parse_id_token("a.b").is_none());
+        assert!(parse_id_token("a.!!!.c").is_none());
+        assert!(parse_id_token("a.b.c.d").is_none());
+    }
+}
@@ -0,0 +1,201 @@
+//! Account identity - recognizes stored records that hold the same login
+
+use sha2::{Digest, Sha256};
+
+use crate::types::{AuthData, IdentityMatch, StoredAccount};
+
+/// Short, non-reversible fingerprint of an API key, safe to log and compare
+pub fn api_key_fingerprint(key: &str) -> String {
+    let digest = Sha256::digest(key.trim().as_bytes());
+    digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn chatgpt_account_id(account: &StoredAccount) -> Option<&str> {
+    match &account.auth_data {
+        AuthData::ChatGPT { account_id, .. } => account_id.as_deref().filter(|id| !id.is_empty()),
+        AuthData::ApiKey { .. } => None,
+    }
+}
+
+fn emails_conflict(a: &StoredAccount, b: &StoredAccount) -> bool {
+    match (a.email.as_deref(), b.email.as_deref()) {
+        (Some(a), Some(b)) => !a.eq_ignore_ascii_case(b),
+        _ => false,
+    }
+}
+
+fn emails_and_plans_match(a: &StoredAccount, b: &StoredAccount) -> bool {
+    match (
+        a.email.as_deref(),
+        b.email.as_deref(),
+        a.plan_type.as_deref(),
+        b.plan_type.as_deref(),
+    ) {
+        (Some(email_a), Some(email_b), Some(plan_a), Some(plan_b)) => {
+            email_a.eq_ignore_ascii_case(email_b) && plan_a.eq_ignore_ascii_case(plan_b)
+        }
+        _ => false,
+    }
+}
+
+/// How two records were recognized as the same login.
+///
+/// ChatGPT records match on `chatgpt_account_id` when both have one. Team
+/// workspaces share that ID between members, so records with different emails
+/// never match. Without an account ID on both sides, email plus plan decides.
+/// API key records match on the key fingerprint.
+pub fn identity_match(a: &StoredAccount, b: &StoredAccount) -> Option<IdentityMatch> {
+    match (&a.auth_data, &b.auth_data) {
+        (AuthData::ApiKey { key: key_a }, AuthData::ApiKey { key: key_b }) => {
+            (api_key_fingerprint(key_a) == api_key_fingerprint(key_b))
+                .then_some(IdentityMatch::ApiKey)
+        }
+        (AuthData::ChatGPT { .. }, AuthData::ChatGPT { .. }) => {
+            match (chatgpt_account_id(a), chatgpt_account_id(b)) {
+                (Some(id_a), Some(id_b)) => (id_a == id_b && !emails_conflict(a, b))
+                    .then_some(IdentityMatch::ChatgptAccountId),
+                _ => emails_and_plans_match(a, b).then_some(IdentityMatch::EmailAndPlan),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether two records hold the same login
+pub fn is_same_identity(a: &StoredAccount, b: &StoredAccount) -> bool {
+    identity_match(a, b).is_some()
+}
+
+/// Find a stored record, other than `candidate` itself, that holds the same login
+pub fn find_duplicate_account<'a>(
+    accounts: &'a [StoredAccount],
+    candidate: &StoredAccount,
+) -> Option<&'a StoredAccount> {
+    accounts
+        .iter()
+        .find(|account| account.id != candidate.id && is_same_identity(account, candidate))
+}
+
+/// Group indexes of records that hold the same login; only groups of two or more are returned
+pub(crate) fn group_duplicate_indexes(accounts: &[StoredAccount]) -> Vec<Vec<usize>> {
+    let mut group_of: Vec<Option<usize>> = vec![None; accounts.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+
+    for i in 0..accounts.len() {
+        for j in (i + 1)..accounts.len() {
+            if group_of[j].is_some() || !is_same_identity(&accounts[i], &accounts[j]) {
+                continue;
+            }
+
+            let group = *group_of[i].get_or_insert_with(|| {
+                groups.push(vec![i]);
+                groups.len() - 1
+            });
+            groups[group].push(j);
+            group_of[j] = Some(group);
+        }
+    }
+
+    groups
+}
+
+/// Copy credentials from an incoming record into an existing one, keeping its ID and name
+pub(crate) fn merge_credentials_into(existing: &mut StoredAccount, incoming: &StoredAccount) {
+    existing.auth_mode = incoming.auth_mode;
+    existing.auth_data = incoming.auth_data.clone();
+
+    if incoming.email.is_some() {
+        existing.email = incoming.email.clone();
+    }
+
+    if incoming.plan_type.is_some() {
+        existing.plan_type = incoming.plan_type.clone();
+    }
+
+    existing.refresh_organizations();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{api_key_fingerprint, group_duplicate_indexes, identity_match, is_same_identity};
+    use crate::types::IdentityMatch;
+    use crate::types::StoredAccount;
+
+    fn chatgpt(
+        email: Option<&str>,
+        account_id: Option<&str>,
+        refresh_token: &str,
+    ) -> StoredAccount {
+        StoredAccount::new_chatgpt(
+            "Account".to_string(),
+            email.map(String::from),
+            Some("plus".to_string()),
+            "id-token".to_string(),
+            "access-token".to_string(),
+            refresh_token.to_string(),
+            account_id.map(String::from),
+        )
+    }
+
+    #[test]
+    fn fingerprint_is_stable_and_hides_the_key() {
+        let fingerprint = api_key_fingerprint("sk-test-123");
+
+        assert_eq!(fingerprint, api_key_fingerprint(" sk-test-123 "));
+        assert_eq!(fingerprint.len(), 16);
+        assert!(!fingerprint.contains("sk-"));
+    }
+
+    #[test]
+    fn same_chatgpt_account_id_is_a_duplicate_despite_diverging_tokens() {
+        let first = chatgpt(Some("me@example.com"), Some("acct-1"), "refresh-a");
+        let second = chatgpt(Some("ME@example.com"), Some("acct-1"), "refresh-b");
+
+        assert!(is_same_identity(&first, &second));
+    }
+
+    #[test]
+    fn team_members_sharing_a_workspace_are_not_duplicates() {
+        let first = chatgpt(Some("alice@example.com"), Some("team-1"), "refresh-a");
+        let second = chatgpt(Some("bob@example.com"), Some("team-1"), "refresh-b");
+
+        assert!(!is_same_identity(&first, &second));
+    }
+
+    #[test]
+    fn api_keys_match_by_fingerprint() {
+        let first = StoredAccount::new_api_key("One".to_string(), "sk-same".to_string());
+        let second = StoredAccount::new_api_key("Two".to_string(), "sk-same".to_string());
+        let other = StoredAccount::new_api_key("Three".to_string(), "sk-other".to_string());
+
+        assert!(is_same_identity(&first, &second));
+        assert!(!is_same_identity(&first, &other));
+    }
+
+    #[test]
+    fn falls_back_to_email_and_plan_without_account_ids() {
+        let first = chatgpt(Some("me@example.com"), None, "refresh-a");
+        let second = chatgpt(Some("me@example.com"), Some("acct-1"), "refresh-b");
+
+        assert_eq!(
+            identity_match(&first, &second),
+            Some(IdentityMatch::EmailAndPlan)
+        );
+    }
+
+    #[test]
+    fn groups_every_copy_of_a_login_together() {
+        let accounts = vec![
+            chatgpt(Some("me@example.com"), Some("acct-1"), "refresh-a"),
+            StoredAccount::new_api_key("Key".to_string(), "sk-one".to_string()),
+            chatgpt(Some("me@example.com"), Some("acct-1"), "refresh-b"),
+            chatgpt(Some("other@example.com"), Some("acct-2"), "refresh-c"),
+            chatgpt(Some("me@example.com"), Some("acct-1"), "refresh-d"),
+        ];
+
+        assert_eq!(group_duplicate_indexes(&accounts), vec![vec![0, 2, 4]]);
+    }
+}
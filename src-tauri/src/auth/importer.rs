@@ -0,0 +1,255 @@
+//! Bulk importer - adds every auth.json-shaped file in a directory as an account
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::auth::audit::record_audit;
+use crate::auth::identity::{is_same_identity, merge_credentials_into};
+use crate::auth::storage::{load_accounts, save_accounts};
+use crate::auth::switcher::{
+    account_from_auth_json, account_has_usable_credentials, read_auth_json_file,
+};
+use crate::types::{
+    AccountsStore, AuditAction, BulkImportFileResult, BulkImportReport, BulkImportStatus,
+    DuplicateStrategy, StoredAccount,
+};
+
+/// Pick a display name for an imported account that is unique in the store.
+///
+/// Prefers "email (plan)", then the email, then the plan, then the file name without extension.
+pub(crate) fn derive_account_name(
+    account: &StoredAccount,
+    file_stem: &str,
+    existing: &[StoredAccount],
+) -> String {
+    let base = match (account.email.as_deref(), account.plan_type.as_deref()) {
+        (Some(email), Some(plan)) => format!("{email} ({plan})"),
+        (Some(email), None) => email.to_string(),
+        (None, Some(plan)) => plan.to_string(),
+        _ => file_stem.to_string(),
+    };
+
+    unique_account_name(base, existing)
+}
+
+/// Append a numeric suffix to `base` until no stored account uses the name
+pub(crate) fn unique_account_name(base: String, existing: &[StoredAccount]) -> String {
+    let is_taken = |name: &str| existing.iter().any(|a| a.name == name);
+    if !is_taken(&base) {
+        return base;
+    }
+
+    (2..)
+        .map(|suffix| format!("{base} {suffix}"))
+        .find(|name| !is_taken(name))
+        .expect("unbounded suffix range always yields a free name")
+}
+
+fn list_json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read import directory: {}", dir.display()))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("Failed to read import directory: {}", dir.display()))?
+            .path();
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json && path.is_file() {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn file_result(
+    path: &Path,
+    status: BulkImportStatus,
+    account: Option<&StoredAccount>,
+    message: Option<String>,
+) -> BulkImportFileResult {
+    BulkImportFileResult {
+        file_name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.display().to_string(),
+        status,
+        account_id: account.map(|a| a.id.clone()),
+        account_name: account.map(|a| a.name.clone()),
+        message,
+    }
+}
+
+fn import_file_into_store(
+    store: &mut AccountsStore,
+    path: &Path,
+    on_duplicate: DuplicateStrategy,
+) -> BulkImportFileResult {
+    let path_str = path.display().to_string();
+    let candidate = match read_auth_json_file(&path_str)
+        .and_then(|auth| account_from_auth_json(auth, String::new()))
+    {
+        Ok(account) => account,
+        Err(err) => {
+            return file_result(
+                path,
+                BulkImportStatus::Failed,
+                None,
+                Some(format!("{err:#}")),
+            )
+        }
+    };
+
+    if !account_has_usable_credentials(&candidate) {
+        return file_result(
+            path,
+            BulkImportStatus::Failed,
+            None,
+            Some("File contains empty or placeholder credentials".to_string()),
+        );
+    }
+
+    if let Some(existing) = store
+        .accounts
+        .iter_mut()
+        .find(|account| is_same_identity(account, &candidate))
+    {
+        return match on_duplicate {
+            DuplicateStrategy::Skip => file_result(
+                path,
+                BulkImportStatus::Skipped,
+                Some(existing),
+                Some(format!("Already stored as '{}'", existing.name)),
+            ),
+            DuplicateStrategy::Merge => {
+                merge_credentials_into(existing, &candidate);
+                file_result(
+                    path,
+                    BulkImportStatus::Merged,
+                    Some(existing),
+                    Some(format!("Updated credentials of '{}'", existing.name)),
+                )
+            }
+        };
+    }
+
+    let file_stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported account".to_string());
+
+    let mut account = candidate;
+    account.name = derive_account_name(&account, &file_stem, &store.accounts);
+    let result = file_result(path, BulkImportStatus::Imported, Some(&account), None);
+    store.accounts.push(account);
+    result
+}
+
+/// Import every `*.json` file in a directory as an account.
+///
+/// Files holding a login that is already stored (including one imported earlier
+/// in the same pass) are skipped or merged according to `on_duplicate`. The store
+/// is saved once after all files are processed.
+pub fn import_accounts_from_directory(
+    dir: &str,
+    on_duplicate: DuplicateStrategy,
+) -> Result<BulkImportReport> {
+    let dir_path = Path::new(dir);
+    if !dir_path.is_dir() {
+        anyhow::bail!("Not a directory: {dir}");
+    }
+
+    let files = list_json_files(dir_path)?;
+    let mut store = load_accounts()?;
+
+    let results: Vec<BulkImportFileResult> = files
+        .iter()
+        .map(|path| import_file_into_store(&mut store, path, on_duplicate))
+        .collect();
+
+    let changed = results.iter().any(|result| {
+        matches!(
+            result.status,
+            BulkImportStatus::Imported | BulkImportStatus::Merged
+        )
+    });
+
+    if changed {
+        if store.active_account_id.is_none() {
+            store.active_account_id = store.accounts.first().map(|a| a.id.clone());
+        }
+        save_accounts(&store)?;
+
+        for result in &results {
+            let action = match result.status {
+                BulkImportStatus::Imported => AuditAction::AccountAdded,
+                BulkImportStatus::Merged => AuditAction::CredentialsUpdated,
+                _ => continue,
+            };
+            let account = store
+                .accounts
+                .iter()
+                .find(|a| result.account_id.as_deref() == Some(a.id.as_str()));
+            record_audit(
+                action,
+                account,
+                Some(format!("Imported from {}", result.file_name)),
+            );
+        }
+    }
+
+    println!(
+        "[Import] Processed {} file(s) from {}",
+        results.len(),
+        dir_path.display()
+    );
+
+    Ok(BulkImportReport::new(dir.to_string(), results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive_account_name;
+    use crate::types::StoredAccount;
+
+    #[test]
+    fn derives_unique_names_from_email_plan_or_file_name() {
+        let with_email = StoredAccount::new_chatgpt(
+            String::new(),
+            Some("me@example.com".to_string()),
+            Some("pro".to_string()),
+            "id".to_string(),
+            "access".to_string(),
+            "refresh".to_string(),
+            None,
+        );
+        let plan_only = StoredAccount::new_chatgpt(
+            String::new(),
+            None,
+            Some("plus".to_string()),
+            "id".to_string(),
+            "access".to_string(),
+            "refresh".to_string(),
+            None,
+        );
+        let api_key = StoredAccount::new_api_key(String::new(), "sk-test".to_string());
+        let existing = vec![StoredAccount::new_api_key(
+            "me@example.com (pro)".to_string(),
+            "sk-other".to_string(),
+        )];
+
+        assert_eq!(
+            derive_account_name(&with_email, "auth", &existing),
+            "me@example.com (pro) 2"
+        );
+        assert_eq!(derive_account_name(&plan_only, "auth", &existing), "plus");
+        assert_eq!(derive_account_name(&api_key, "ci-key", &existing), "ci-key");
+    }
+}
@@ -0,0 +1,178 @@
+//! Switch journal - records account switches so the last one can be undone
+
+use std::collections::HashSet;
+use std::fs;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::auth::audit::record_audit;
+use crate::auth::storage::{
+    get_switch_journal_file, load_accounts, save_accounts, write_private_file,
+};
+use crate::auth::switcher::{
+    capture_outgoing_auth, get_codex_auth_file, write_account_auth_json, write_auth_json_content,
+};
+use crate::types::{
+    AuditAction, PreviousAuthState, StoredAccount, SwitchJournal, SwitchJournalEntry,
+    SwitchUndoResult,
+};
+
+/// Older entries are dropped once the journal grows past this size
+const MAX_JOURNAL_ENTRIES: usize = 50;
+
+/// Load the switch journal from disk
+pub fn load_switch_journal() -> Result<SwitchJournal> {
+    let path = get_switch_journal_file()?;
+
+    if !path.exists() {
+        return Ok(SwitchJournal::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read switch journal: {}", path.display()))?;
+
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse switch journal: {}", path.display()))
+}
+
+fn save_switch_journal(journal: &SwitchJournal) -> Result<()> {
+    let path = get_switch_journal_file()?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(journal).context("Failed to serialize switch journal")?;
+
+    write_private_file(&path, content.as_bytes())
+        .with_context(|| format!("Failed to write switch journal: {}", path.display()))
+}
+
+/// Append a switch to the journal
+pub(crate) fn record_switch(
+    previous_active_account_id: Option<String>,
+    account_id: String,
+    previous_auth: PreviousAuthState,
+) -> Result<()> {
+    let mut journal = load_switch_journal()?;
+
+    journal.entries.push(SwitchJournalEntry {
+        id: Uuid::new_v4().to_string(),
+        switched_at: Utc::now(),
+        previous_active_account_id,
+        account_id,
+        previous_auth,
+    });
+
+    if journal.entries.len() > MAX_JOURNAL_ENTRIES {
+        let overflow = journal.entries.len() - MAX_JOURNAL_ENTRIES;
+        journal.entries.drain(..overflow);
+    }
+
+    save_switch_journal(&journal)
+}
+
+/// Snapshot paths the journal still needs to undo a switch
+pub(crate) fn journal_snapshot_paths() -> Result<HashSet<String>> {
+    let journal = load_switch_journal()?;
+    Ok(journal
+        .entries
+        .into_iter()
+        .filter_map(|entry| match entry.previous_auth {
+            PreviousAuthState::Snapshot { path } => Some(path),
+            _ => None,
+        })
+        .collect())
+}
+
+/// What auth.json should hold after an undo
+enum RestoreTarget {
+    Remove,
+    Account(Box<StoredAccount>),
+    Content(String),
+}
+
+/// Undo the most recent switch, restoring auth.json and the active account together
+pub fn undo_last_switch() -> Result<SwitchUndoResult> {
+    let mut journal = load_switch_journal()?;
+    let entry = journal
+        .entries
+        .last()
+        .cloned()
+        .context("No account switch to undo")?;
+
+    let mut store = load_accounts()?;
+
+    // Resolve everything that can fail before auth.json is touched
+    let target = match &entry.previous_auth {
+        PreviousAuthState::Missing => RestoreTarget::Remove,
+        PreviousAuthState::StoredAccount { account_id } => {
+            let account = store
+                .accounts
+                .iter()
+                .find(|a| a.id == *account_id)
+                .cloned()
+                .with_context(|| {
+                    format!(
+                        "The account used before the last switch no longer exists: {account_id}"
+                    )
+                })?;
+            RestoreTarget::Account(Box::new(account))
+        }
+        PreviousAuthState::Snapshot { path } => {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read pre-switch snapshot: {path}"))?;
+            RestoreTarget::Content(content)
+        }
+    };
+
+    // The current file may hold tokens Codex refreshed since the switch
+    let replaced_auth = capture_outgoing_auth(&store.accounts)?;
+
+    match target {
+        RestoreTarget::Remove => {
+            let auth_path = get_codex_auth_file()?;
+            if auth_path.exists() {
+                fs::remove_file(&auth_path).with_context(|| {
+                    format!("Failed to remove auth.json: {}", auth_path.display())
+                })?;
+            }
+        }
+        RestoreTarget::Account(account) => write_account_auth_json(&account)?,
+        RestoreTarget::Content(content) => write_auth_json_content(&content)?,
+    }
+
+    store.active_account_id = entry
+        .previous_active_account_id
+        .clone()
+        .filter(|id| store.accounts.iter().any(|a| a.id == *id));
+    save_accounts(&store)?;
+
+    journal.entries.pop();
+    save_switch_journal(&journal)?;
+
+    let restored = store
+        .active_account_id
+        .as_deref()
+        .and_then(|id| store.accounts.iter().find(|a| a.id == id));
+    let undone_name = store
+        .accounts
+        .iter()
+        .find(|a| a.id == entry.account_id)
+        .map_or(entry.account_id.as_str(), |a| a.name.as_str());
+    record_audit(
+        AuditAction::SwitchUndone,
+        restored,
+        Some(format!("Undid switch to '{undone_name}'")),
+    );
+
+    Ok(SwitchUndoResult {
+        undone: entry,
+        active_account_id: store.active_account_id,
+        replaced_auth,
+    })
+}
@@ -1,9 +1,39 @@
 //! Authentication module
 
+pub mod activity;
+pub mod audit;
+pub mod bundle;
+pub mod claims;
+pub mod identity;
+pub mod importer;
+pub mod journal;
+pub mod notes;
+pub mod oauth_pages;
 pub mod oauth_server;
+pub mod query;
+pub mod revoke;
+pub mod snapshots;
 pub mod storage;
 pub mod switcher;
+pub mod tags;
+pub mod tokens;
+pub mod trash;
 
+pub use activity::*;
+pub use audit::*;
+pub use bundle::*;
+pub use claims::*;
+pub use identity::*;
+pub use importer::*;
+pub use journal::*;
+pub use notes::*;
+pub use oauth_pages::*;
 pub use oauth_server::*;
+pub use query::*;
+pub use revoke::*;
+pub use snapshots::*;
 pub use storage::*;
 pub use switcher::*;
+pub use tags::*;
+pub use tokens::*;
+pub use trash::*;
@@ -0,0 +1,236 @@
+//! Account notes and custom metadata - user annotations that never reach auth.json
+
+use anyhow::{Context, Result};
+
+use crate::auth::storage::{load_accounts, save_accounts};
+use crate::types::{AuthMode, StoredAccount};
+
+const MAX_NOTES_LENGTH: usize = 4000;
+const MAX_METADATA_KEY_LENGTH: usize = 64;
+const MAX_METADATA_VALUE_LENGTH: usize = 1024;
+const MAX_METADATA_FIELDS: usize = 50;
+
+/// Trim notes, treating blank notes as none
+pub(crate) fn normalize_notes(notes: Option<String>) -> Result<Option<String>> {
+    let Some(notes) = notes.map(|notes| notes.trim().to_string()) else {
+        return Ok(None);
+    };
+
+    if notes.is_empty() {
+        return Ok(None);
+    }
+    if notes.chars().count() > MAX_NOTES_LENGTH {
+        anyhow::bail!("Notes are longer than {MAX_NOTES_LENGTH} characters");
+    }
+
+    Ok(Some(notes))
+}
+
+/// Set or remove one metadata field on an account; blank values remove the field
+pub(crate) fn apply_metadata_field(
+    account: &mut StoredAccount,
+    key: &str,
+    value: Option<String>,
+) -> Result<()> {
+    let key = key.trim();
+    if key.is_empty() {
+        anyhow::bail!("Metadata keys cannot be empty");
+    }
+    if key.chars().count() > MAX_METADATA_KEY_LENGTH {
+        anyhow::bail!("Metadata key '{key}' is longer than {MAX_METADATA_KEY_LENGTH} characters");
+    }
+
+    let value = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    match value {
+        Some(value) => {
+            if value.chars().count() > MAX_METADATA_VALUE_LENGTH {
+                anyhow::bail!(
+                    "Value of '{key}' is longer than {MAX_METADATA_VALUE_LENGTH} characters"
+                );
+            }
+            if !account.metadata.contains_key(key) && account.metadata.len() >= MAX_METADATA_FIELDS
+            {
+                anyhow::bail!("Accounts can have at most {MAX_METADATA_FIELDS} metadata fields");
+            }
+            account.metadata.insert(key.to_string(), value);
+        }
+        None => {
+            account.metadata.remove(key);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fold the notes and metadata of merged duplicates into `account`.
+///
+/// Distinct notes are appended after the account's own; metadata keys are
+/// united, with the account's own values winning.
+pub(crate) fn merge_annotations(account: &mut StoredAccount, duplicates: &[StoredAccount]) {
+    let mut notes: Vec<&str> = account.notes.as_deref().into_iter().collect();
+    for duplicate in duplicates {
+        if let Some(duplicate_notes) = duplicate.notes.as_deref() {
+            if !notes.contains(&duplicate_notes) {
+                notes.push(duplicate_notes);
+            }
+        }
+    }
+    let notes = (!notes.is_empty()).then(|| notes.join("\n\n"));
+
+    for duplicate in duplicates {
+        for (key, value) in &duplicate.metadata {
+            account
+                .metadata
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+    account.notes = notes;
+}
+
+/// Whether `text` appears, ignoring case, in the account's name, email, plan, auth mode,
+/// tags, notes or metadata
+pub fn account_matches_text(account: &StoredAccount, text: &str) -> bool {
+    let needle = text.trim().to_lowercase();
+    if needle.is_empty() {
+        return true;
+    }
+
+    let contains = |haystack: &str| haystack.to_lowercase().contains(&needle);
+
+    contains(&account.name)
+        || account.email.as_deref().is_some_and(contains)
+        || account.plan_type.as_deref().is_some_and(contains)
+        || contains(match account.auth_mode {
+            AuthMode::ApiKey => "api_key",
+            AuthMode::ChatGPT => "chatgpt",
+        })
+        || account.notes.as_deref().is_some_and(contains)
+        || account.tags.iter().any(|tag| contains(tag))
+        || account
+            .metadata
+            .iter()
+            .any(|(key, value)| contains(key) || contains(value))
+}
+
+fn update_account<F>(account_id: &str, update: F) -> Result<StoredAccount>
+where
+    F: FnOnce(&mut StoredAccount) -> Result<()>,
+{
+    let mut store = load_accounts()?;
+
+    let account = store
+        .accounts
+        .iter_mut()
+        .find(|a| a.id == account_id)
+        .context("Account not found")?;
+    update(account)?;
+
+    let updated = account.clone();
+    save_accounts(&store)?;
+    Ok(updated)
+}
+
+/// Replace an account's notes; `None` or blank notes clear them
+pub fn set_account_notes(account_id: &str, notes: Option<String>) -> Result<StoredAccount> {
+    let notes = normalize_notes(notes)?;
+    update_account(account_id, |account| {
+        account.notes = notes;
+        Ok(())
+    })
+}
+
+/// Set one custom metadata field, or remove it when `value` is `None` or blank
+pub fn set_account_metadata_field(
+    account_id: &str,
+    key: &str,
+    value: Option<String>,
+) -> Result<StoredAccount> {
+    update_account(account_id, |account| {
+        apply_metadata_field(account, key, value)
+    })
+}
+
+/// Stored accounts whose name, email, plan, auth mode, tags, notes or metadata contain `text`
+pub fn search_accounts(text: &str) -> Result<Vec<StoredAccount>> {
+    let store = load_accounts()?;
+    Ok(store
+        .accounts
+        .into_iter()
+        .filter(|account| account_matches_text(account, text))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_matches_text, apply_metadata_field, merge_annotations, normalize_notes};
+    use crate::types::StoredAccount;
+
+    #[test]
+    fn sets_and_removes_metadata_fields() {
+        let mut account = StoredAccount::new_api_key("CI".to_string(), "sk-ci".to_string());
+
+        apply_metadata_field(&mut account, " cost center ", Some(" R&D-42 ".to_string())).unwrap();
+        apply_metadata_field(&mut account, "owner", Some("alice".to_string())).unwrap();
+        assert_eq!(account.metadata["cost center"], "R&D-42");
+
+        apply_metadata_field(&mut account, "owner", Some("  ".to_string())).unwrap();
+        assert!(!account.metadata.contains_key("owner"));
+        assert!(apply_metadata_field(&mut account, " ", Some("x".to_string())).is_err());
+
+        assert_eq!(normalize_notes(Some("   ".to_string())).unwrap(), None);
+    }
+
+    #[test]
+    fn merges_notes_and_metadata_of_duplicates() {
+        let account = |notes: Option<&str>, metadata: &[(&str, &str)]| {
+            let mut account = StoredAccount::new_api_key("CI".to_string(), "sk-ci".to_string());
+            account.notes = notes.map(str::to_string);
+            account.metadata = metadata
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            account
+        };
+        let mut primary = account(Some("billing: finance"), &[("owner", "alice")]);
+        let duplicates = [
+            account(
+                Some("billing: finance"),
+                &[("owner", "bob"), ("team", "ml")],
+            ),
+            account(Some("shared with CI"), &[]),
+            account(None, &[("cost center", "cc-42")]),
+        ];
+
+        merge_annotations(&mut primary, &duplicates);
+
+        assert_eq!(
+            primary.notes.as_deref(),
+            Some("billing: finance\n\nshared with CI")
+        );
+        assert_eq!(primary.metadata["owner"], "alice");
+        assert_eq!(primary.metadata["team"], "ml");
+        assert_eq!(primary.metadata["cost center"], "cc-42");
+
+        let mut bare = account(None, &[]);
+        merge_annotations(&mut bare, &duplicates[1..2]);
+        assert_eq!(bare.notes.as_deref(), Some("shared with CI"));
+    }
+
+    #[test]
+    fn matches_notes_and_metadata_case_insensitively() {
+        let mut account = StoredAccount::new_api_key("CI".to_string(), "sk-ci".to_string());
+        account.notes = Some("Do not use for CI".to_string());
+        account
+            .metadata
+            .insert("billing owner".to_string(), "Finance".to_string());
+
+        assert!(account_matches_text(&account, "not use"));
+        assert!(account_matches_text(&account, "FINANCE"));
+        assert!(account_matches_text(&account, "billing"));
+        assert!(!account_matches_text(&account, "sk-ci"));
+    }
+}
@@ -0,0 +1,133 @@
+//! OAuth callback pages - what the browser shows when a login comes back
+
+/// Served with every callback response; the pages only need inline styles and the favicon
+pub const CONTENT_SECURITY_POLICY: &str = "default-src 'none'; style-src 'unsafe-inline'; img-src 'self'; base-uri 'none'; form-action 'none'; frame-ancestors 'none'";
+
+/// The app icon, served as the callback pages' favicon
+pub const FAVICON_PNG: &[u8] = include_bytes!("../../icons/32x32.png");
+
+const CALLBACK_TEMPLATE: &str = include_str!("pages/callback.html");
+
+/// Page shown for a callback request.
+///
+/// Pages only contain fixed text: nothing from the request or from the token
+/// endpoint is echoed back to the browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallbackPage {
+    Success,
+    /// The user declined or cancelled at the sign-in screen
+    Cancelled,
+    /// The callback belongs to another login attempt
+    StateMismatch,
+    MissingCode,
+    /// The provider returned an error other than a cancellation
+    ProviderError,
+    ExchangeFailed,
+}
+
+impl CallbackPage {
+    /// Page for an `error` returned by the provider
+    pub fn for_provider_error(error: &str) -> Self {
+        match error {
+            "access_denied" | "login_required" | "consent_required" => Self::Cancelled,
+            _ => Self::ProviderError,
+        }
+    }
+
+    pub fn status_code(self) -> u16 {
+        match self {
+            Self::Success | Self::Cancelled => 200,
+            Self::StateMismatch | Self::MissingCode | Self::ProviderError => 400,
+            Self::ExchangeFailed => 502,
+        }
+    }
+
+    fn text(self) -> (&'static str, &'static str, &'static str, &'static str) {
+        match self {
+            Self::Success => (
+                "Login Successful",
+                "Login successful!",
+                "Your account is connected.",
+                "You can close this window and return to Codex Usage Inspector.",
+            ),
+            Self::Cancelled => (
+                "Login Cancelled",
+                "Login cancelled",
+                "Sign-in was cancelled before access was granted, so nothing was changed.",
+                "To try again, start the login from Codex Usage Inspector.",
+            ),
+            Self::StateMismatch => (
+                "Login Link Expired",
+                "This login link is no longer valid",
+                "The sign-in response belongs to a different or earlier login attempt.",
+                "Close this window and start a new login from Codex Usage Inspector.",
+            ),
+            Self::MissingCode => (
+                "Login Incomplete",
+                "Login incomplete",
+                "The sign-in response did not include an authorization code.",
+                "Start the login again from Codex Usage Inspector.",
+            ),
+            Self::ProviderError => (
+                "Login Failed",
+                "Sign-in failed",
+                "OpenAI could not complete the sign-in.",
+                "Start the login again from Codex Usage Inspector. The app shows the details.",
+            ),
+            Self::ExchangeFailed => (
+                "Login Failed",
+                "Could not finish signing in",
+                "Sign-in worked, but the app could not exchange it for credentials.",
+                "Check your connection and start the login again from Codex Usage Inspector. The app shows the details.",
+            ),
+        }
+    }
+
+    pub fn render(self) -> String {
+        let (title, heading, message, guidance) = self.text();
+        let (icon, accent) = match self {
+            Self::Success => ("&#10003;", "#16a34a"),
+            Self::Cancelled => ("&#8617;", "#6b7280"),
+            _ => ("&#9888;", "#dc2626"),
+        };
+
+        CALLBACK_TEMPLATE
+            .replace("{{title}}", title)
+            .replace("{{heading}}", heading)
+            .replace("{{message}}", message)
+            .replace("{{guidance}}", guidance)
+            .replace("{{icon}}", icon)
+            .replace("{{accent}}", accent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CallbackPage;
+
+    #[test]
+    fn renders_every_page_from_the_template() {
+        for page in [
+            CallbackPage::Success,
+            CallbackPage::Cancelled,
+            CallbackPage::StateMismatch,
+            CallbackPage::MissingCode,
+            CallbackPage::ProviderError,
+            CallbackPage::ExchangeFailed,
+        ] {
+            let html = page.render();
+            assert!(!html.contains("{{"), "{page:?} left a placeholder");
+            assert!(html.contains("Codex Usage Inspector"));
+        }
+
+        assert!(CallbackPage::Success.render().contains("Login successful!"));
+        assert_eq!(
+            CallbackPage::for_provider_error("access_denied"),
+            CallbackPage::Cancelled
+        );
+        assert_eq!(
+            CallbackPage::for_provider_error("<script>"),
+            CallbackPage::ProviderError
+        );
+    }
+}
@@ -1,21 +1,24 @@
 //! Local OAuth server for handling ChatGPT login flow
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use base64::Engine;
 use rand::RngCore;
 use sha2::{Digest, Sha256};
-use tiny_http::{Header, Request, Response, Server};
-use tokio::sync::oneshot;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
+use tokio::sync::{mpsc, oneshot, Notify};
+use tokio_util::sync::CancellationToken;
 
-use crate::types::{OAuthLoginInfo, StoredAccount};
+use crate::auth::claims::parse_id_token;
+use crate::auth::oauth_pages::{CallbackPage, CONTENT_SECURITY_POLICY, FAVICON_PNG};
+use crate::types::{OAuthLoginInfo, OAuthLoginPhase, OAuthLoginProgress, StoredAccount};
 
-const DEFAULT_ISSUER: &str = "https://auth.openai.com";
-const CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
+pub(crate) const DEFAULT_ISSUER: &str = "https://auth.openai.com";
+pub(crate) const CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
 const DEFAULT_PORT: u16 = 1455; // Same as official Codex
 
 /// PKCE codes for OAuth
@@ -124,169 +127,328 @@ async fn exchange_code_for_tokens(
     Ok(tokens)
 }
 
-/// Parse claims from JWT ID token
-fn parse_id_token_claims(id_token: &str) -> (Option<String>, Option<String>, Option<String>) {
-    let parts: Vec<&str> = id_token.split('.').collect();
-    if parts.len() != 3 {
-        return (None, None, None);
-    }
+/// Build an account from a token response, reading email, plan and account ID from the id token
+fn account_from_tokens(account_name: &str, tokens: TokenResponse) -> StoredAccount {
+    let claims = parse_id_token(&tokens.id_token).unwrap_or_default();
+
+    StoredAccount::new_chatgpt(
+        account_name.to_string(),
+        claims.email(),
+        claims.plan_type(),
+        tokens.id_token,
+        tokens.access_token,
+        tokens.refresh_token,
+        claims.account_id(),
+    )
+}
 
-    let payload = match base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(parts[1]) {
-        Ok(bytes) => bytes,
-        Err(_) => return (None, None, None),
-    };
+/// OAuth login flow result
+pub struct OAuthLoginResult {
+    pub account: StoredAccount,
+}
 
-    let json: serde_json::Value = match serde_json::from_slice(&payload) {
-        Ok(v) => v,
-        Err(_) => return (None, None, None),
-    };
+/// How long a callback login waits for the browser before giving up
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long a connected client may take to send its request line and headers
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Largest request head the callback server reads
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+
+/// Receives progress of a callback login as it happens
+pub type OAuthProgressReporter = Arc<dyn Fn(OAuthLoginProgress) + Send + Sync>;
+
+/// A callback login running on the async runtime
+pub struct OAuthCallbackLogin {
+    pub info: OAuthLoginInfo,
+    pub rx: oneshot::Receiver<Result<OAuthLoginResult>>,
+    /// Cancelling stops the listener and any token exchange in flight
+    pub cancel: CancellationToken,
+}
+
+/// A callback request handed to the login whose `state` it carries
+struct CallbackRequest {
+    stream: TcpStream,
+    target: String,
+}
+
+struct CallbackRoute {
+    state: String,
+    tx: mpsc::UnboundedSender<CallbackRequest>,
+}
 
-    let email = json.get("email").and_then(|v| v.as_str()).map(String::from);
+struct ActiveListener {
+    port: u16,
+    /// Wakes the listener task to check whether any login still waits
+    idle: Arc<Notify>,
+}
 
-    let auth_claims = json.get("https://api.openai.com/auth");
+struct RouterState {
+    routes: Vec<CallbackRoute>,
+    listener: Option<ActiveListener>,
+}
 
-    let plan_type = auth_claims
-        .and_then(|auth| auth.get("chatgpt_plan_type"))
-        .and_then(|v| v.as_str())
-        .map(String::from);
+/// The callback listener shared by every login in progress.
+///
+/// The issuer only redirects to `localhost:1455`, so concurrent logins share one
+/// listener that hands each callback to the login whose `state` it carries. The
+/// listener stops, releasing the port, once no login is waiting.
+struct CallbackRouter {
+    port: u16,
+    state: Mutex<RouterState>,
+}
 
-    let account_id = auth_claims
-        .and_then(|auth| auth.get("chatgpt_account_id"))
-        .and_then(|v| v.as_str())
-        .map(String::from);
+static CALLBACK_ROUTER: CallbackRouter = CallbackRouter::new(DEFAULT_PORT);
 
-    (email, plan_type, account_id)
+/// Callbacks routed to one login; dropping it stops routing them
+struct CallbackInbox {
+    router: &'static CallbackRouter,
+    state: String,
+    port: u16,
+    requests: mpsc::UnboundedReceiver<CallbackRequest>,
 }
 
-/// OAuth login flow result
-pub struct OAuthLoginResult {
-    pub account: StoredAccount,
+impl Drop for CallbackInbox {
+    fn drop(&mut self) {
+        self.router.unregister(&self.state);
+    }
 }
 
-/// Start the OAuth login flow
+impl CallbackRouter {
+    const fn new(port: u16) -> Self {
+        Self {
+            port,
+            state: Mutex::new(RouterState {
+                routes: Vec::new(),
+                listener: None,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RouterState> {
+        self.state.lock().unwrap()
+    }
+
+    fn bind(&self) -> Result<TcpListener> {
+        let bind = || -> std::io::Result<TcpListener> {
+            let socket = TcpSocket::new_v4()?;
+            // Rebinding right after the previous listener stopped must not fail on TIME_WAIT
+            #[cfg(unix)]
+            socket.set_reuseaddr(true)?;
+            socket.bind(SocketAddr::from(([127, 0, 0, 1], self.port)))?;
+            socket.listen(128)
+        };
+
+        bind().with_context(|| {
+            format!(
+                "Failed to start OAuth server on port {}; another Codex login may be using it",
+                self.port
+            )
+        })
+    }
+
+    /// Route callbacks carrying `state` to the returned inbox, starting the listener if needed
+    fn register(&'static self, state: &str) -> Result<CallbackInbox> {
+        let mut router = self.lock();
+
+        let port = match &router.listener {
+            Some(listener) => listener.port,
+            None => {
+                let listener = self.bind()?;
+                let port = listener
+                    .local_addr()
+                    .context("Failed to determine server port")?
+                    .port();
+                let idle = Arc::new(Notify::new());
+                tokio::spawn(self.serve(listener, idle.clone()));
+                router.listener = Some(ActiveListener { port, idle });
+                println!("[OAuth] Server started on port {port}");
+                port
+            }
+        };
+
+        let (tx, requests) = mpsc::unbounded_channel();
+        router.routes.push(CallbackRoute {
+            state: state.to_string(),
+            tx,
+        });
+
+        Ok(CallbackInbox {
+            router: self,
+            state: state.to_string(),
+            port,
+            requests,
+        })
+    }
+
+    fn unregister(&self, state: &str) {
+        let mut router = self.lock();
+        router.routes.retain(|route| route.state != state);
+
+        if router.routes.is_empty() {
+            if let Some(listener) = &router.listener {
+                listener.idle.notify_one();
+            }
+        }
+    }
+
+    /// Accept callback connections until no login is waiting anymore
+    async fn serve(&'static self, listener: TcpListener, idle: Arc<Notify>) {
+        loop {
+            let accepted = tokio::select! {
+                _ = idle.notified() => None,
+                accepted = listener.accept() => Some(accepted),
+            };
+
+            match accepted {
+                Some(Ok((stream, _))) => {
+                    tokio::spawn(self.dispatch(stream));
+                }
+                Some(Err(err)) => println!("[OAuth] Failed to accept callback connection: {err}"),
+                None => {
+                    let mut router = self.lock();
+                    if router.routes.is_empty() {
+                        // Close the port before a new login can try to bind it again
+                        router.listener = None;
+                        drop(listener);
+                        println!("[OAuth] Server stopped");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Read one request and hand it to the login it belongs to
+    async fn dispatch(&'static self, mut stream: TcpStream) {
+        let target = match tokio::time::timeout(
+            REQUEST_READ_TIMEOUT,
+            read_request_target(&mut stream),
+        )
+        .await
+        {
+            Ok(Some(target)) => target,
+            _ => {
+                let _ = write_text(&mut stream, 400, "Bad Request").await;
+                return;
+            }
+        };
+
+        let Ok(parsed) = url::Url::parse(&format!("http://localhost{target}")) else {
+            let _ = write_text(&mut stream, 400, "Bad Request").await;
+            return;
+        };
+
+        match parsed.path() {
+            "/auth/callback" => {}
+            "/favicon.ico" => {
+                let _ = write_response(&mut stream, 200, "image/png", FAVICON_PNG).await;
+                return;
+            }
+            _ => {
+                let _ = write_text(&mut stream, 404, "Not Found").await;
+                return;
+            }
+        }
+
+        let state = parsed
+            .query_pairs()
+            .find(|(key, _)| key == "state")
+            .map(|(_, value)| value.into_owned());
+        let route = state.and_then(|state| {
+            self.lock()
+                .routes
+                .iter()
+                .find(|route| route.state == state)
+                .map(|route| route.tx.clone())
+        });
+
+        let request = CallbackRequest { stream, target };
+        let unrouted = match route {
+            Some(tx) => tx.send(request).err().map(|err| err.0),
+            None => Some(request),
+        };
+
+        // A callback for a login that ended or was never started here
+        if let Some(mut request) = unrouted {
+            println!("[OAuth] Callback for an unknown login attempt, ignoring");
+            let _ = write_page(&mut request.stream, CallbackPage::StateMismatch).await;
+        }
+    }
+}
+
+/// Start the OAuth login flow.
+///
+/// The login waits on the shared callback listener as a task on the current runtime
+/// and ends on success, failure, cancellation or after [`LOGIN_TIMEOUT`].
 pub async fn start_oauth_login(
     account_name: String,
-) -> Result<(
-    OAuthLoginInfo,
-    oneshot::Receiver<Result<OAuthLoginResult>>,
-    Arc<AtomicBool>,
-)> {
+    on_progress: OAuthProgressReporter,
+) -> Result<OAuthCallbackLogin> {
     let pkce = generate_pkce();
     let state = generate_state();
 
     println!("[OAuth] Starting login for account: {account_name}");
     println!("[OAuth] PKCE challenge: {}", &pkce.code_challenge[..20]);
 
-    // Try official default port first; fall back to a random free port if it is busy.
-    let server = match Server::http(format!("127.0.0.1:{DEFAULT_PORT}")) {
-        Ok(server) => server,
-        Err(default_err) => {
-            println!(
-                "[OAuth] Default callback port {DEFAULT_PORT} unavailable ({default_err}), using a random local port"
-            );
-            Server::http("127.0.0.1:0").map_err(|fallback_err| {
-                anyhow::anyhow!(
-                    "Failed to start OAuth server: default port {DEFAULT_PORT} error: {default_err}; fallback error: {fallback_err}"
-                )
-            })?
-        }
-    };
-
-    let actual_port = match server.server_addr().to_ip() {
-        Some(addr) => addr.port(),
-        None => anyhow::bail!("Failed to determine server port"),
-    };
+    let inbox = CALLBACK_ROUTER.register(&state)?;
+    let actual_port = inbox.port;
 
     let redirect_uri = format!("http://localhost:{actual_port}/auth/callback");
     let auth_url = build_authorize_url(DEFAULT_ISSUER, CLIENT_ID, &redirect_uri, &pkce, &state);
 
-    println!("[OAuth] Server started on port {actual_port}");
     println!("[OAuth] Redirect URI: {redirect_uri}");
     println!("[OAuth] Auth URL: {auth_url}");
 
     let login_info = OAuthLoginInfo {
+        login_id: uuid::Uuid::new_v4().to_string(),
         auth_url: auth_url.clone(),
         callback_port: actual_port,
+        redirect_uri: redirect_uri.clone(),
     };
 
-    // Create a channel for the result
     let (tx, rx) = oneshot::channel();
-    let cancelled = Arc::new(AtomicBool::new(false));
-
-    // Spawn the server in a background thread
-    let server = Arc::new(server);
-    let pkce_clone = pkce.clone();
-    let state_clone = state.clone();
-    let cancelled_clone = cancelled.clone();
-
-    thread::spawn(move || {
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let result = runtime.block_on(run_oauth_server(
-            server,
-            pkce_clone,
-            state_clone,
-            redirect_uri,
-            account_name,
-            cancelled_clone,
-        ));
+    let cancel = CancellationToken::new();
+
+    let server = CallbackServer {
+        login_id: login_info.login_id.clone(),
+        pkce,
+        expected_state: state,
+        redirect_uri,
+        account_name,
+        on_progress: on_progress.clone(),
+    };
+    let task_cancel = cancel.clone();
+
+    tokio::spawn(async move {
+        let result = server.run(inbox, task_cancel).await;
         let _ = tx.send(result);
     });
 
-    // Open the browser
-    let _ = webbrowser::open(&auth_url);
+    // Open the browser; the login then waits for it to come back
+    match webbrowser::open(&auth_url) {
+        Ok(()) => on_progress(OAuthLoginProgress {
+            login_id: login_info.login_id.clone(),
+            phase: OAuthLoginPhase::WaitingForBrowser,
+            reason: None,
+        }),
+        Err(err) => println!("[OAuth] Failed to open browser: {err}"),
+    }
 
-    Ok((login_info, rx, cancelled))
+    Ok(OAuthCallbackLogin {
+        info: login_info,
+        rx,
+        cancel,
+    })
 }
 
-/// Run the OAuth callback server
-async fn run_oauth_server(
-    server: Arc<Server>,
+/// State of one callback listener
+struct CallbackServer {
+    login_id: String,
     pkce: PkceCodes,
     expected_state: String,
     redirect_uri: String,
     account_name: String,
-    cancelled: Arc<AtomicBool>,
-) -> Result<OAuthLoginResult> {
-    let timeout = Duration::from_secs(300); // 5 minute timeout
-    let start = std::time::Instant::now();
-
-    loop {
-        if cancelled.load(Ordering::Relaxed) {
-            anyhow::bail!("OAuth login cancelled");
-        }
-
-        if start.elapsed() > timeout {
-            anyhow::bail!("OAuth login timed out");
-        }
-
-        // Use recv_timeout to allow checking the timeout
-        let request = match server.recv_timeout(Duration::from_secs(1)) {
-            Ok(Some(req)) => req,
-            Ok(None) => continue,
-            Err(_) => continue,
-        };
-
-        let result = handle_oauth_request(
-            request,
-            &pkce,
-            &expected_state,
-            &redirect_uri,
-            &account_name,
-        )
-        .await;
-
-        match result {
-            HandleResult::Continue => continue,
-            HandleResult::Success(account) => {
-                server.unblock();
-                return Ok(OAuthLoginResult { account });
-            }
-            HandleResult::Error(e) => {
-                server.unblock();
-                return Err(e);
-            }
-        }
-    }
+    on_progress: OAuthProgressReporter,
 }
 
 enum HandleResult {
@@ -295,26 +457,65 @@ enum HandleResult {
     Error(anyhow::Error),
 }
 
-async fn handle_oauth_request(
-    request: Request,
-    pkce: &PkceCodes,
-    expected_state: &str,
-    redirect_uri: &str,
-    account_name: &str,
-) -> HandleResult {
-    let url_str = request.url().to_string();
-    let parsed = match url::Url::parse(&format!("http://localhost{url_str}")) {
-        Ok(u) => u,
-        Err(_) => {
-            let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
-            return HandleResult::Continue;
+impl CallbackServer {
+    fn report(&self, phase: OAuthLoginPhase, reason: Option<String>) {
+        (self.on_progress)(OAuthLoginProgress {
+            login_id: self.login_id.clone(),
+            phase,
+            reason,
+        });
+    }
+
+    /// Handle routed callbacks until the login succeeds, fails, is cancelled or times out
+    async fn run(
+        &self,
+        mut inbox: CallbackInbox,
+        cancel: CancellationToken,
+    ) -> Result<OAuthLoginResult> {
+        let deadline = tokio::time::sleep(LOGIN_TIMEOUT);
+        tokio::pin!(deadline);
+
+        loop {
+            let handled = tokio::select! {
+                _ = cancel.cancelled() => {
+                    self.report(OAuthLoginPhase::Cancelled, None);
+                    anyhow::bail!("OAuth login cancelled")
+                }
+                _ = &mut deadline => {
+                    self.report(OAuthLoginPhase::TimedOut, None);
+                    anyhow::bail!("OAuth login timed out")
+                }
+                request = inbox.requests.recv() => match request {
+                    Some(mut request) => self.handle_request(&mut request.stream, &request.target).await,
+                    None => anyhow::bail!("OAuth callback server stopped"),
+                },
+            };
+
+            match handled {
+                HandleResult::Continue => continue,
+                HandleResult::Success(account) => {
+                    self.report(OAuthLoginPhase::Succeeded, None);
+                    return Ok(OAuthLoginResult { account });
+                }
+                HandleResult::Error(e) => {
+                    self.report(OAuthLoginPhase::Failed, Some(format!("{e:#}")));
+                    return Err(e);
+                }
+            }
         }
-    };
+    }
 
-    let path = parsed.path();
+    async fn handle_request(&self, stream: &mut TcpStream, target: &str) -> HandleResult {
+        let parsed = match url::Url::parse(&format!("http://localhost{target}")) {
+            Ok(u) => u,
+            Err(_) => {
+                let _ = write_text(stream, 400, "Bad Request").await;
+                return HandleResult::Continue;
+            }
+        };
 
-    if path == "/auth/callback" {
         println!("[OAuth] Received callback request");
+        self.report(OAuthLoginPhase::CallbackReceived, None);
         let params: std::collections::HashMap<String, String> =
             parsed.query_pairs().into_owned().collect();
 
@@ -323,6 +524,21 @@ async fn handle_oauth_request(
             params.keys().collect::<Vec<_>>()
         );
 
+        // The router only hands over callbacks with this login's state; check it anyway
+        // so a stray request can never end this login.
+        if params.get("state").map(String::as_str) != Some(self.expected_state.as_str()) {
+            println!("[OAuth] State mismatch, ignoring callback");
+            let _ = write_page(stream, CallbackPage::StateMismatch).await;
+            self.report(
+                OAuthLoginPhase::WaitingForBrowser,
+                Some("Ignored a callback for a different login attempt".to_string()),
+            );
+            return HandleResult::Continue;
+        }
+
+        println!("[OAuth] State verified OK");
+        self.report(OAuthLoginPhase::StateVerified, None);
+
         // Check for error response
         if let Some(error) = params.get("error") {
             let error_desc = params
@@ -330,99 +546,258 @@ async fn handle_oauth_request(
                 .map(|s| s.as_str())
                 .unwrap_or("Unknown error");
             println!("[OAuth] Error from provider: {error} - {error_desc}");
-            let _ = request.respond(
-                Response::from_string(format!("OAuth Error: {error} - {error_desc}"))
-                    .with_status_code(400),
-            );
+            let _ = write_page(stream, CallbackPage::for_provider_error(error)).await;
             return HandleResult::Error(anyhow::anyhow!("OAuth error: {error} - {error_desc}"));
         }
 
-        // Verify state
-        if params.get("state").map(String::as_str) != Some(expected_state) {
-            println!("[OAuth] State mismatch!");
-            let _ = request.respond(Response::from_string("State mismatch").with_status_code(400));
-            return HandleResult::Error(anyhow::anyhow!("OAuth state mismatch"));
-        }
-
-        println!("[OAuth] State verified OK");
-
         // Get the authorization code
         let code = match params.get("code") {
             Some(c) if !c.is_empty() => c.clone(),
             _ => {
                 println!("[OAuth] Missing authorization code");
-                let _ = request.respond(
-                    Response::from_string("Missing authorization code").with_status_code(400),
-                );
+                let _ = write_page(stream, CallbackPage::MissingCode).await;
                 return HandleResult::Error(anyhow::anyhow!("Missing authorization code"));
             }
         };
 
         println!("[OAuth] Got authorization code, exchanging for tokens...");
+        self.report(OAuthLoginPhase::ExchangingTokens, None);
 
         // Exchange code for tokens
-        match exchange_code_for_tokens(DEFAULT_ISSUER, CLIENT_ID, redirect_uri, pkce, &code).await {
+        match exchange_code_for_tokens(
+            DEFAULT_ISSUER,
+            CLIENT_ID,
+            &self.redirect_uri,
+            &self.pkce,
+            &code,
+        )
+        .await
+        {
             Ok(tokens) => {
                 println!("[OAuth] Token exchange successful!");
-                // Parse claims from ID token
-                let (email, plan_type, chatgpt_account_id) =
-                    parse_id_token_claims(&tokens.id_token);
-
-                // Create the account
-                let account = StoredAccount::new_chatgpt(
-                    account_name.to_string(),
-                    email,
-                    plan_type,
-                    tokens.id_token,
-                    tokens.access_token,
-                    tokens.refresh_token,
-                    chatgpt_account_id,
-                );
-
-                // Send success response
-                let success_html = r#"<!DOCTYPE html>
-<html>
-<head>
-    <title>Login Successful</title>
-    <style>
-        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; display: flex; justify-content: center; align-items: center; height: 100vh; margin: 0; background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); }
-        .container { text-align: center; background: white; padding: 40px 60px; border-radius: 16px; box-shadow: 0 20px 60px rgba(0,0,0,0.3); }
-        h1 { color: #333; margin-bottom: 10px; }
-        p { color: #666; }
-        .checkmark { font-size: 48px; margin-bottom: 20px; }
-    </style>
-</head>
-<body>
-    <div class="container">
-        <div class="checkmark">✓</div>
-        <h1>Login Successful!</h1>
-        <p>You can close this window and return to Codex Usage Inspector.</p>
-    </div>
-</body>
-</html>"#;
-
-                let response = Response::from_string(success_html).with_header(
-                    Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..])
-                        .unwrap(),
-                );
-                let _ = request.respond(response);
-
-                return HandleResult::Success(account);
+                let account = account_from_tokens(&self.account_name, tokens);
+                let _ = write_page(stream, CallbackPage::Success).await;
+                HandleResult::Success(account)
             }
             Err(e) => {
                 println!("[OAuth] Token exchange failed: {e}");
-                let _ = request.respond(
-                    Response::from_string(format!("Token exchange failed: {e}"))
-                        .with_status_code(500),
-                );
-                return HandleResult::Error(e);
+                let _ = write_page(stream, CallbackPage::ExchangeFailed).await;
+                HandleResult::Error(e)
             }
         }
     }
+}
+
+/// Read an HTTP request head and return the request target of a GET request
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return None;
+        }
+        let read = stream.read(&mut buf).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    parse_request_target(&head)
+}
+
+/// The target of a `GET <target> HTTP/1.x` request line
+pub(crate) fn parse_request_target(head: &[u8]) -> Option<String> {
+    let head = std::str::from_utf8(head).ok()?;
+    let request_line = head.lines().next()?;
+    let mut parts = request_line.split_whitespace();
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("GET"), Some(target), Some(version))
+            if target.starts_with('/') && version.starts_with("HTTP/1.") =>
+        {
+            Some(target.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Write a complete response with security headers and close the connection.
+///
+/// Callback URLs carry the authorization code, so pages must not leak it through
+/// referrers or caches.
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        502 => "Bad Gateway",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {status} {reason}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Content-Security-Policy: {CONTENT_SECURITY_POLICY}\r\n\
+         X-Content-Type-Options: nosniff\r\n\
+         Referrer-Policy: no-referrer\r\n\
+         Cache-Control: no-store\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    );
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
+
+async fn write_page(stream: &mut TcpStream, page: CallbackPage) -> std::io::Result<()> {
+    write_response(
+        stream,
+        page.status_code(),
+        "text/html; charset=utf-8",
+        page.render().as_bytes(),
+    )
+    .await
+}
+
+async fn write_text(stream: &mut TcpStream, status: u16, text: &str) -> std::io::Result<()> {
+    write_response(stream, status, "text/plain; charset=utf-8", text.as_bytes()).await
+}
+
+/// A login finished by pasting the redirect URL instead of a local callback
+pub struct ManualOAuthFlow {
+    pkce: PkceCodes,
+    state: String,
+    redirect_uri: String,
+    account_name: String,
+}
+
+/// Start a login for machines whose browser cannot reach the local callback port.
+///
+/// No server is started and no browser is opened. After signing in, the browser is
+/// redirected to a localhost URL that fails to load; the user pastes that URL back,
+/// which [`ManualOAuthFlow::authorization_code`] checks.
+pub fn start_manual_oauth_login(account_name: String) -> (OAuthLoginInfo, ManualOAuthFlow) {
+    let pkce = generate_pkce();
+    let state = generate_state();
+
+    // The authorize endpoint only accepts the registered localhost redirect
+    let redirect_uri = format!("http://localhost:{DEFAULT_PORT}/auth/callback");
+    let auth_url = build_authorize_url(DEFAULT_ISSUER, CLIENT_ID, &redirect_uri, &pkce, &state);
+
+    println!("[OAuth] Starting manual login for account: {account_name}");
+    println!("[OAuth] Auth URL: {auth_url}");
+
+    let login_info = OAuthLoginInfo {
+        login_id: uuid::Uuid::new_v4().to_string(),
+        auth_url,
+        callback_port: DEFAULT_PORT,
+        redirect_uri: redirect_uri.clone(),
+    };
+
+    let flow = ManualOAuthFlow {
+        pkce,
+        state,
+        redirect_uri,
+        account_name,
+    };
+
+    (login_info, flow)
+}
+
+/// Extract the authorization code from a pasted redirect URL, query string or `code#state`.
+///
+/// The paste must carry a `state` matching the login attempt; a bare code is rejected
+/// because it cannot be checked.
+pub(crate) fn parse_pasted_authorization(input: &str, expected_state: &str) -> Result<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        anyhow::bail!("Paste the URL your browser was redirected to");
+    }
+
+    let query_url = if input.contains("://") {
+        Some(url::Url::parse(input).context("The pasted text is not a valid URL")?)
+    } else if input.contains('=') {
+        let query = input.trim_start_matches(['/', '?']);
+        let query = query.split_once('?').map_or(query, |(_, query)| query);
+        Some(
+            url::Url::parse(&format!("http://localhost/?{query}"))
+                .context("The pasted text is not a valid query string")?,
+        )
+    } else {
+        None
+    };
+
+    let Some(url) = query_url else {
+        // `code#state` as shown by some copy helpers
+        return match input.split_once('#') {
+            Some((_, state)) if state != expected_state => anyhow::bail!(
+                "OAuth state mismatch: the pasted code belongs to a different login attempt"
+            ),
+            Some((code, _)) if !code.is_empty() => Ok(code.to_string()),
+            Some(_) => anyhow::bail!("Missing authorization code"),
+            None => anyhow::bail!(
+                "Paste the full URL your browser was redirected to; a code alone cannot be checked against this login"
+            ),
+        };
+    };
+
+    let params: std::collections::HashMap<String, String> =
+        url.query_pairs().into_owned().collect();
+
+    if let Some(error) = params.get("error") {
+        let description = params
+            .get("error_description")
+            .map(String::as_str)
+            .unwrap_or("Unknown error");
+        anyhow::bail!("OAuth error: {error} - {description}");
+    }
+
+    match params.get("state") {
+        Some(state) if state == expected_state => {}
+        Some(_) => anyhow::bail!(
+            "OAuth state mismatch: the pasted URL belongs to a different login attempt"
+        ),
+        None => anyhow::bail!("The pasted URL has no state parameter"),
+    }
+
+    match params.get("code") {
+        Some(code) if !code.is_empty() => Ok(code.clone()),
+        _ => anyhow::bail!("Missing authorization code"),
+    }
+}
+
+impl ManualOAuthFlow {
+    /// The authorization code in a paste, checked against this login attempt
+    pub fn authorization_code(&self, pasted: &str) -> Result<String> {
+        parse_pasted_authorization(pasted, &self.state)
+    }
+}
 
-    // Handle other paths
-    let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
-    HandleResult::Continue
+/// Finish a manual login by exchanging the pasted authorization code
+pub async fn complete_manual_oauth_login(
+    flow: ManualOAuthFlow,
+    code: &str,
+) -> Result<StoredAccount> {
+    println!("[OAuth] Got pasted authorization code, exchanging for tokens...");
+    let tokens = exchange_code_for_tokens(
+        DEFAULT_ISSUER,
+        CLIENT_ID,
+        &flow.redirect_uri,
+        &flow.pkce,
+        code,
+    )
+    .await?;
+    println!("[OAuth] Token exchange successful!");
+
+    Ok(account_from_tokens(&flow.account_name, tokens))
 }
 
 /// Wait for the OAuth login to complete
@@ -432,3 +807,156 @@ pub async fn wait_for_oauth_login(
     let result = rx.await.context("OAuth login was cancelled")??;
     Ok(result.account)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        generate_pkce, parse_pasted_authorization, parse_request_target, CallbackRouter,
+        CallbackServer,
+    };
+    use crate::types::OAuthLoginPhase;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_util::sync::CancellationToken;
+
+    #[test]
+    fn extracts_code_from_pasted_redirects() {
+        let url = "http://localhost:1455/auth/callback?code=ac_123&scope=openid&state=st-1";
+
+        assert_eq!(parse_pasted_authorization(url, "st-1").unwrap(), "ac_123");
+        assert_eq!(
+            parse_pasted_authorization("  /auth/callback?code=ac_123&state=st-1 \n", "st-1")
+                .unwrap(),
+            "ac_123"
+        );
+        assert_eq!(
+            parse_pasted_authorization("code=ac_123&state=st-1", "st-1").unwrap(),
+            "ac_123"
+        );
+        assert_eq!(
+            parse_pasted_authorization("ac_123#st-1", "st-1").unwrap(),
+            "ac_123"
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_state_and_provider_errors() {
+        let foreign = "http://localhost:1455/auth/callback?code=ac_123&state=other";
+        let no_state = "http://localhost:1455/auth/callback?code=ac_123";
+        let denied =
+            "http://localhost:1455/auth/callback?error=access_denied&error_description=Denied&state=st-1";
+
+        assert!(parse_pasted_authorization(foreign, "st-1")
+            .unwrap_err()
+            .to_string()
+            .contains("state mismatch"));
+        assert!(parse_pasted_authorization(no_state, "st-1").is_err());
+        assert!(parse_pasted_authorization(denied, "st-1")
+            .unwrap_err()
+            .to_string()
+            .contains("access_denied"));
+        assert!(parse_pasted_authorization("ac_123#other", "st-1").is_err());
+        // A bare code has no state to check
+        assert!(parse_pasted_authorization("ac_123", "st-1").is_err());
+        assert!(parse_pasted_authorization("#st-1", "st-1").is_err());
+        assert!(parse_pasted_authorization("   ", "st-1").is_err());
+    }
+
+    #[test]
+    fn parses_get_request_targets_only() {
+        assert_eq!(
+            parse_request_target(b"GET /auth/callback?code=a&state=b HTTP/1.1\r\nHost: x\r\n\r\n")
+                .as_deref(),
+            Some("/auth/callback?code=a&state=b")
+        );
+        assert!(parse_request_target(b"POST /auth/callback HTTP/1.1\r\n\r\n").is_none());
+        assert!(parse_request_target(b"GET http://evil/ HTTP/1.1\r\n\r\n").is_none());
+        assert!(parse_request_target(b"garbage").is_none());
+    }
+
+    async fn get(port: u16, target: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream
+            .write_all(format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.unwrap();
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    fn callback_server(
+        login_id: &str,
+        state: &str,
+        port: u16,
+    ) -> (CallbackServer, Arc<Mutex<Vec<OAuthLoginPhase>>>) {
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let recorded = phases.clone();
+        let server = CallbackServer {
+            login_id: login_id.to_string(),
+            pkce: generate_pkce(),
+            expected_state: state.to_string(),
+            redirect_uri: format!("http://localhost:{port}/auth/callback"),
+            account_name: "Work".to_string(),
+            on_progress: Arc::new(move |progress| recorded.lock().unwrap().push(progress.phase)),
+        };
+        (server, phases)
+    }
+
+    #[tokio::test]
+    async fn routes_callbacks_of_concurrent_logins_by_state() {
+        static ROUTER: CallbackRouter = CallbackRouter::new(0);
+
+        let first_inbox = ROUTER.register("st-1").unwrap();
+        let second_inbox = ROUTER.register("st-2").unwrap();
+        let port = first_inbox.port;
+        assert_eq!(second_inbox.port, port);
+
+        let (first, first_phases) = callback_server("login-1", "st-1", port);
+        let (second, second_phases) = callback_server("login-2", "st-2", port);
+        let first_cancel = CancellationToken::new();
+        let task_cancel = first_cancel.clone();
+        let first_task = tokio::spawn(async move { first.run(first_inbox, task_cancel).await });
+        let second_task =
+            tokio::spawn(async move { second.run(second_inbox, CancellationToken::new()).await });
+
+        assert!(get(port, "/favicon.ico").await.starts_with("HTTP/1.1 200"));
+        assert!(get(port, "/other").await.starts_with("HTTP/1.1 404"));
+        let mismatch = get(port, "/auth/callback?code=a&state=other").await;
+        assert!(mismatch.starts_with("HTTP/1.1 400"));
+        assert!(mismatch.contains("Content-Security-Policy: default-src 'none'"));
+        assert!(mismatch.contains("This login link is no longer valid"));
+
+        // The second login gets its own callback; the first keeps waiting
+        let denied = get(port, "/auth/callback?error=access_denied&state=st-2").await;
+        assert!(denied.starts_with("HTTP/1.1 200"));
+        let error = second_task.await.unwrap().err().expect("denied login");
+        assert!(error.to_string().contains("access_denied"));
+        assert!(!first_task.is_finished());
+
+        first_cancel.cancel();
+        let error = first_task.await.unwrap().err().expect("cancelled login");
+        assert!(error.to_string().contains("cancelled"));
+
+        assert_eq!(*first_phases.lock().unwrap(), [OAuthLoginPhase::Cancelled]);
+        assert_eq!(
+            *second_phases.lock().unwrap(),
+            [
+                OAuthLoginPhase::CallbackReceived,
+                OAuthLoginPhase::StateVerified,
+                OAuthLoginPhase::Failed,
+            ]
+        );
+
+        // The port is released once no login is waiting
+        for _ in 0..100 {
+            if ROUTER.lock().listener.is_none() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(ROUTER.lock().listener.is_none());
+        assert!(TcpListener::bind(("127.0.0.1", port)).await.is_ok());
+    }
+}
@@ -0,0 +1,263 @@
+//! Account queries - filtering and sorting the store on the backend
+
+use std::cmp::Ordering;
+
+use crate::auth::notes::account_matches_text;
+use crate::auth::tags::account_has_tag;
+use crate::types::{AccountQuery, AccountSortKey, StoredAccount, UsageInfo};
+
+/// Whether `value` lies within the optional bounds; a missing value never matches a bound
+fn within(value: Option<f64>, min: Option<f64>, max: Option<f64>) -> bool {
+    if min.is_none() && max.is_none() {
+        return true;
+    }
+    value.is_some_and(|value| {
+        min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+    })
+}
+
+fn matches_query(
+    account: &StoredAccount,
+    usage: Option<&UsageInfo>,
+    active_id: Option<&str>,
+    query: &AccountQuery,
+) -> bool {
+    let is_active = active_id == Some(account.id.as_str());
+
+    query
+        .text
+        .as_deref()
+        .is_none_or(|text| account_matches_text(account, text))
+        && query.plan_type.as_deref().is_none_or(|plan| {
+            account
+                .plan_type
+                .as_deref()
+                .is_some_and(|p| p.eq_ignore_ascii_case(plan.trim()))
+        })
+        && query.auth_mode.is_none_or(|mode| account.auth_mode == mode)
+        && query.is_active.is_none_or(|active| is_active == active)
+        && query
+            .tag
+            .as_deref()
+            .is_none_or(|tag| account_has_tag(account, tag))
+        && query
+            .last_used_after
+            .is_none_or(|after| account.last_used_at.is_some_and(|used| used >= after))
+        && query
+            .last_used_before
+            .is_none_or(|before| account.last_used_at.is_none_or(|used| used < before))
+        && within(
+            usage.and_then(|u| u.primary_used_percent),
+            query.min_primary_used_percent,
+            query.max_primary_used_percent,
+        )
+        && within(
+            usage.and_then(|u| u.secondary_used_percent),
+            query.min_secondary_used_percent,
+            query.max_secondary_used_percent,
+        )
+}
+
+/// Compare two optional sort values, keeping missing ones last in either direction
+fn compare_present_first<T: PartialOrd>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn compare_accounts(
+    (a, a_usage): &(&StoredAccount, Option<&UsageInfo>),
+    (b, b_usage): &(&StoredAccount, Option<&UsageInfo>),
+    sort_by: AccountSortKey,
+    descending: bool,
+) -> Ordering {
+    let usage_field = |field: fn(&UsageInfo) -> Option<f64>| {
+        compare_present_first(a_usage.and_then(field), b_usage.and_then(field), descending)
+    };
+    let reset_field = |field: fn(&UsageInfo) -> Option<i64>| {
+        compare_present_first(a_usage.and_then(field), b_usage.and_then(field), descending)
+    };
+
+    match sort_by {
+        AccountSortKey::Stored => Ordering::Equal,
+        AccountSortKey::Name => compare_present_first(
+            Some(a.name.to_lowercase()),
+            Some(b.name.to_lowercase()),
+            descending,
+        ),
+        AccountSortKey::LastUsed => {
+            compare_present_first(a.last_used_at, b.last_used_at, descending)
+        }
+        AccountSortKey::CreatedAt => {
+            compare_present_first(Some(a.created_at), Some(b.created_at), descending)
+        }
+        AccountSortKey::PrimaryUsage => usage_field(|u| u.primary_used_percent),
+        AccountSortKey::SecondaryUsage => usage_field(|u| u.secondary_used_percent),
+        AccountSortKey::PrimaryResetsAt => reset_field(|u| u.primary_resets_at),
+        AccountSortKey::SecondaryResetsAt => reset_field(|u| u.secondary_resets_at),
+    }
+}
+
+/// Filter and sort accounts, pairing each with its latest usage.
+///
+/// Ties keep the stored order; `descending` with the stored order reverses it.
+pub fn query_accounts<'a>(
+    accounts: &'a [StoredAccount],
+    usage: &'a [UsageInfo],
+    active_id: Option<&str>,
+    query: &AccountQuery,
+) -> Vec<(&'a StoredAccount, Option<&'a UsageInfo>)> {
+    let mut matches: Vec<(&StoredAccount, Option<&UsageInfo>)> = accounts
+        .iter()
+        .map(|account| {
+            let usage = usage.iter().find(|u| u.account_id == account.id);
+            (account, usage)
+        })
+        .filter(|(account, usage)| matches_query(account, *usage, active_id, query))
+        .collect();
+
+    if query.sort_by == AccountSortKey::Stored {
+        if query.descending {
+            matches.reverse();
+        }
+    } else {
+        matches.sort_by(|a, b| compare_accounts(a, b, query.sort_by, query.descending));
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::query_accounts;
+    use crate::types::{AccountQuery, AccountSortKey, AuthMode, StoredAccount, UsageInfo};
+    use chrono::{Duration, Utc};
+
+    fn account(name: &str, plan: &str) -> StoredAccount {
+        StoredAccount::new_chatgpt(
+            name.to_string(),
+            Some(format!("{}@example.com", name.to_lowercase())),
+            Some(plan.to_string()),
+            "id".to_string(),
+            "access".to_string(),
+            "refresh".to_string(),
+            None,
+        )
+    }
+
+    fn usage(account: &StoredAccount, primary: f64, resets_at: i64) -> UsageInfo {
+        let mut usage = UsageInfo::error(account.id.clone(), String::new());
+        usage.error = None;
+        usage.primary_used_percent = Some(primary);
+        usage.primary_resets_at = Some(resets_at);
+        usage
+    }
+
+    fn names(matches: &[(&StoredAccount, Option<&UsageInfo>)]) -> Vec<String> {
+        matches.iter().map(|(a, _)| a.name.clone()).collect()
+    }
+
+    #[test]
+    fn filters_by_text_plan_mode_and_activity() {
+        let mut work = account("Work", "pro");
+        let home = account("Home", "plus");
+        let key = StoredAccount::new_api_key("Key".to_string(), "sk-key".to_string());
+        work.last_used_at = Some(Utc::now() - Duration::days(2));
+        let accounts = vec![work.clone(), home, key];
+
+        let query =
+            |query: AccountQuery| names(&query_accounts(&accounts, &[], Some(&work.id), &query));
+
+        assert_eq!(
+            query(AccountQuery {
+                text: Some("EXAMPLE".to_string()),
+                ..Default::default()
+            }),
+            ["Work", "Home"]
+        );
+        assert_eq!(
+            query(AccountQuery {
+                plan_type: Some("Plus".to_string()),
+                ..Default::default()
+            }),
+            ["Home"]
+        );
+        assert_eq!(
+            query(AccountQuery {
+                auth_mode: Some(AuthMode::ApiKey),
+                ..Default::default()
+            }),
+            ["Key"]
+        );
+        assert_eq!(
+            query(AccountQuery {
+                is_active: Some(false),
+                ..Default::default()
+            }),
+            ["Home", "Key"]
+        );
+        assert_eq!(
+            query(AccountQuery {
+                last_used_after: Some(Utc::now() - Duration::days(7)),
+                ..Default::default()
+            }),
+            ["Work"]
+        );
+        assert_eq!(
+            query(AccountQuery {
+                last_used_before: Some(Utc::now() - Duration::days(7)),
+                ..Default::default()
+            }),
+            ["Home", "Key"]
+        );
+    }
+
+    #[test]
+    fn applies_usage_thresholds_and_sorts_with_missing_usage_last() {
+        let a = account("A", "pro");
+        let b = account("B", "pro");
+        let c = account("C", "pro");
+        let usage = vec![usage(&a, 80.0, 300), usage(&b, 20.0, 100)];
+        let accounts = vec![a, b, c];
+
+        let below_half = query_accounts(
+            &accounts,
+            &usage,
+            None,
+            &AccountQuery {
+                max_primary_used_percent: Some(50.0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(names(&below_half), ["B"]);
+
+        let by_usage = AccountQuery {
+            sort_by: AccountSortKey::PrimaryUsage,
+            descending: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&query_accounts(&accounts, &usage, None, &by_usage)),
+            ["A", "B", "C"]
+        );
+
+        let by_reset = AccountQuery {
+            sort_by: AccountSortKey::PrimaryResetsAt,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&query_accounts(&accounts, &usage, None, &by_reset)),
+            ["B", "A", "C"]
+        );
+    }
+}
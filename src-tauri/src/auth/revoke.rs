@@ -0,0 +1,210 @@
+//! Token revocation - asks the issuer to invalidate a deleted account's tokens
+
+use std::fs;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+
+use crate::auth::oauth_server::{CLIENT_ID, DEFAULT_ISSUER};
+use crate::auth::storage::{get_revocation_log_file, write_private_file};
+use crate::types::{
+    AuthData, RevokedTokenType, StoredAccount, TokenRevocationLog, TokenRevocationReport,
+    TokenRevocationResult,
+};
+
+/// Older attempts are dropped once the log grows past this size
+const MAX_REVOCATION_LOG_ENTRIES: usize = 100;
+const REVOCATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Revoke one token at `{issuer}/oauth/revoke` (RFC 7009)
+async fn revoke_token(
+    client: &reqwest::Client,
+    issuer: &str,
+    token: &str,
+    token_type: RevokedTokenType,
+) -> TokenRevocationResult {
+    let hint = match token_type {
+        RevokedTokenType::RefreshToken => "refresh_token",
+        RevokedTokenType::AccessToken => "access_token",
+    };
+    let body = format!(
+        "token={}&token_type_hint={hint}&client_id={}",
+        urlencoding::encode(token),
+        urlencoding::encode(CLIENT_ID)
+    );
+
+    let response = client
+        .post(format!("{issuer}/oauth/revoke"))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .timeout(REVOCATION_TIMEOUT)
+        .send()
+        .await;
+
+    // Only the status is kept; response bodies are not recorded
+    let error = match response {
+        Ok(response) if response.status().is_success() => None,
+        Ok(response) => Some(format!(
+            "Revocation endpoint returned {}",
+            response.status()
+        )),
+        Err(err) => Some(format!("Failed to reach revocation endpoint: {err}")),
+    };
+
+    TokenRevocationResult {
+        token_type,
+        revoked: error.is_none(),
+        error,
+    }
+}
+
+pub(crate) async fn revoke_account_tokens_at(
+    issuer: &str,
+    account: &StoredAccount,
+) -> TokenRevocationReport {
+    let mut results = Vec::new();
+
+    if let AuthData::ChatGPT {
+        access_token,
+        refresh_token,
+        ..
+    } = &account.auth_data
+    {
+        let client = reqwest::Client::new();
+        // The refresh token first: it is the long-lived one
+        for (token, token_type) in [
+            (refresh_token, RevokedTokenType::RefreshToken),
+            (access_token, RevokedTokenType::AccessToken),
+        ] {
+            if !token.trim().is_empty() {
+                results.push(revoke_token(&client, issuer, token, token_type).await);
+            }
+        }
+    }
+
+    TokenRevocationReport {
+        account_id: account.id.clone(),
+        account_name: account.name.clone(),
+        attempted_at: Utc::now(),
+        results,
+    }
+}
+
+/// Revoke the refresh and access tokens of a ChatGPT account.
+///
+/// API key accounts have nothing to revoke and get an empty report.
+pub async fn revoke_account_tokens(account: &StoredAccount) -> TokenRevocationReport {
+    revoke_account_tokens_at(DEFAULT_ISSUER, account).await
+}
+
+/// Load the recorded revocation attempts
+pub fn load_revocation_log() -> Result<TokenRevocationLog> {
+    let path = get_revocation_log_file()?;
+
+    if !path.exists() {
+        return Ok(TokenRevocationLog::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read revocation log: {}", path.display()))?;
+
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse revocation log: {}", path.display()))
+}
+
+/// Append a revocation attempt to the log
+pub fn record_revocation(report: &TokenRevocationReport) -> Result<()> {
+    let mut log = load_revocation_log()?;
+    log.entries.push(report.clone());
+
+    if log.entries.len() > MAX_REVOCATION_LOG_ENTRIES {
+        let overflow = log.entries.len() - MAX_REVOCATION_LOG_ENTRIES;
+        log.entries.drain(..overflow);
+    }
+
+    let path = get_revocation_log_file()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(&log).context("Failed to serialize revocation log")?;
+
+    write_private_file(&path, content.as_bytes())
+        .with_context(|| format!("Failed to write revocation log: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::revoke_account_tokens_at;
+    use crate::types::{RevokedTokenType, StoredAccount};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer each request with the next status, recording the request bodies
+    async fn issuer(statuses: Vec<u16>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let issuer = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+
+        let task = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let read = stream.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..read]).into_owned());
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        (issuer, task)
+    }
+
+    fn chatgpt_account() -> StoredAccount {
+        StoredAccount::new_chatgpt(
+            "Work".to_string(),
+            None,
+            None,
+            "id-token".to_string(),
+            "access-token".to_string(),
+            "refresh-token".to_string(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn revokes_refresh_then_access_token_and_records_failures() {
+        let (issuer, requests) = issuer(vec![200, 503]).await;
+
+        let report = revoke_account_tokens_at(&issuer, &chatgpt_account()).await;
+        let requests = requests.await.unwrap();
+
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.results[0].token_type, RevokedTokenType::RefreshToken);
+        assert!(report.results[0].revoked);
+        assert!(!report.results[1].revoked);
+        assert!(report.results[1]
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("503")));
+        assert!(!report.all_revoked());
+        assert!(requests[0].starts_with("POST /oauth/revoke"));
+        assert!(requests[0].contains("token=refresh-token&token_type_hint=refresh_token"));
+    }
+
+    #[tokio::test]
+    async fn api_key_accounts_have_nothing_to_revoke() {
+        let account = StoredAccount::new_api_key("Key".to_string(), "sk-test".to_string());
+
+        let report = revoke_account_tokens_at("http://127.0.0.1:9", &account).await;
+
+        assert!(report.results.is_empty());
+        assert!(report.all_revoked());
+    }
+}
@@ -0,0 +1,334 @@
+//! Session snapshot retention - lists snapshot files and prunes them by policy
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Utc};
+
+use crate::auth::journal::journal_snapshot_paths;
+use crate::auth::storage::{ensure_snapshots_dir, load_settings, save_settings};
+use crate::types::{SnapshotFileInfo, SnapshotPruneReport, SnapshotRetentionPolicy};
+
+const SNAPSHOT_PREFIX: &str = "auth-snapshot-";
+const SNAPSHOT_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const SNAPSHOT_TIMESTAMP_LEN: usize = 16;
+
+/// Read the snapshot creation time encoded in a snapshot filename
+pub(crate) fn parse_snapshot_timestamp(file_name: &str) -> Option<DateTime<Utc>> {
+    let rest = file_name.strip_prefix(SNAPSHOT_PREFIX)?;
+    let timestamp = rest.get(..SNAPSHOT_TIMESTAMP_LEN)?;
+    NaiveDateTime::parse_from_str(timestamp, SNAPSHOT_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+fn is_snapshot_file_name(file_name: &str) -> bool {
+    file_name.starts_with(SNAPSHOT_PREFIX) && file_name.ends_with(".json")
+}
+
+/// List snapshot files in a directory, newest first
+pub(crate) fn list_snapshot_files_in(dir: &Path) -> Result<Vec<SnapshotFileInfo>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read snapshots directory: {}", dir.display()))?;
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read snapshots directory: {}", dir.display()))?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !is_snapshot_file_name(&file_name) || !entry.path().is_file() {
+            continue;
+        }
+
+        // Fall back to the file modification time for renamed snapshots
+        let created_at = parse_snapshot_timestamp(&file_name).or_else(|| {
+            entry
+                .metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .map(DateTime::<Utc>::from)
+        });
+
+        if let Some(created_at) = created_at {
+            snapshots.push(SnapshotFileInfo {
+                file_name,
+                path: entry.path().display().to_string(),
+                created_at,
+            });
+        }
+    }
+
+    sort_newest_first(&mut snapshots);
+    Ok(snapshots)
+}
+
+fn sort_newest_first(snapshots: &mut [SnapshotFileInfo]) {
+    snapshots.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
+}
+
+/// Keep the newest snapshot of each of the first `limit` distinct buckets
+fn keep_bucketed<K: Eq + std::hash::Hash>(
+    snapshots: &[SnapshotFileInfo],
+    limit: u32,
+    bucket_of: impl Fn(&DateTime<Utc>) -> K,
+    keep: &mut HashSet<usize>,
+) {
+    let mut seen = HashSet::new();
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        if seen.len() >= limit as usize {
+            break;
+        }
+        if seen.insert(bucket_of(&snapshot.created_at)) {
+            keep.insert(index);
+        }
+    }
+}
+
+/// Longest snapshot age limit that can be configured, about ten years
+pub const MAX_SNAPSHOT_AGE_DAYS: u32 = 3650;
+
+/// Split snapshots into (kept, pruned) according to a retention policy
+pub(crate) fn plan_snapshot_prune(
+    mut snapshots: Vec<SnapshotFileInfo>,
+    policy: &SnapshotRetentionPolicy,
+    now: DateTime<Utc>,
+) -> (Vec<SnapshotFileInfo>, Vec<SnapshotFileInfo>) {
+    sort_newest_first(&mut snapshots);
+
+    let mut keep: HashSet<usize> = HashSet::new();
+    if policy.has_keep_rules() {
+        if let Some(keep_last) = policy.keep_last {
+            keep.extend(0..snapshots.len().min(keep_last as usize));
+        }
+        if let Some(keep_daily) = policy.keep_daily {
+            keep_bucketed(&snapshots, keep_daily, |time| time.date_naive(), &mut keep);
+        }
+        if let Some(keep_weekly) = policy.keep_weekly {
+            keep_bucketed(
+                &snapshots,
+                keep_weekly,
+                |time| {
+                    let week = time.iso_week();
+                    (week.year(), week.week())
+                },
+                &mut keep,
+            );
+        }
+    } else {
+        keep.extend(0..snapshots.len());
+    }
+
+    // An age limit reaching before the start of time is no limit at all
+    let cutoff = policy
+        .max_age_days
+        .and_then(|days| Duration::try_days(i64::from(days)))
+        .and_then(|age| now.checked_sub_signed(age));
+
+    let mut kept = Vec::new();
+    let mut pruned = Vec::new();
+    for (index, snapshot) in snapshots.into_iter().enumerate() {
+        let too_old = cutoff.is_some_and(|cutoff| snapshot.created_at < cutoff);
+        if keep.contains(&index) && !too_old {
+            kept.push(snapshot);
+        } else {
+            pruned.push(snapshot);
+        }
+    }
+
+    (kept, pruned)
+}
+
+/// List all session snapshots, newest first
+pub fn list_snapshot_files() -> Result<Vec<SnapshotFileInfo>> {
+    let snapshots_dir = ensure_snapshots_dir()?;
+    list_snapshot_files_in(&snapshots_dir)
+}
+
+/// Get the configured snapshot retention policy
+pub fn get_snapshot_retention_policy() -> Result<SnapshotRetentionPolicy> {
+    Ok(load_settings()?.snapshot_retention)
+}
+
+/// Persist a new snapshot retention policy
+pub fn set_snapshot_retention_policy(policy: SnapshotRetentionPolicy) -> Result<()> {
+    if policy.keep_last == Some(0) || policy.keep_daily == Some(0) || policy.keep_weekly == Some(0)
+    {
+        anyhow::bail!("Retention keep counts must be at least 1; leave a rule empty to disable it");
+    }
+    if policy
+        .max_age_days
+        .is_some_and(|days| days > MAX_SNAPSHOT_AGE_DAYS)
+    {
+        anyhow::bail!(
+            "Snapshot age limit must be at most {MAX_SNAPSHOT_AGE_DAYS} days; leave it empty to disable it"
+        );
+    }
+
+    let mut settings = load_settings()?;
+    settings.snapshot_retention = policy;
+    save_settings(&settings)
+}
+
+/// Apply the retention policy to the snapshots directory.
+///
+/// With `dry_run` set, nothing is deleted and the report previews what would be pruned.
+pub fn prune_snapshots(dry_run: bool) -> Result<SnapshotPruneReport> {
+    let policy = get_snapshot_retention_policy()?;
+    let snapshots = list_snapshot_files()?;
+    let (mut kept, planned) = plan_snapshot_prune(snapshots, &policy, Utc::now());
+
+    // Pre-switch snapshots stay while the switch journal may still restore them
+    let protected = journal_snapshot_paths()?;
+    let (protected_snapshots, pruned): (Vec<_>, Vec<_>) = planned
+        .into_iter()
+        .partition(|snapshot| protected.contains(&snapshot.path));
+    kept.extend(protected_snapshots);
+    sort_newest_first(&mut kept);
+
+    if !dry_run {
+        for snapshot in &pruned {
+            fs::remove_file(&snapshot.path)
+                .with_context(|| format!("Failed to delete snapshot file: {}", snapshot.path))?;
+        }
+    }
+
+    Ok(SnapshotPruneReport {
+        dry_run,
+        policy,
+        kept,
+        pruned,
+    })
+}
+
+/// Apply the retention policy after a snapshot was written, logging instead of failing
+pub(crate) fn apply_snapshot_retention() {
+    match prune_snapshots(false) {
+        Ok(report) if !report.pruned.is_empty() => {
+            println!(
+                "[Snapshots] Pruned {} snapshot(s) by retention policy",
+                report.pruned.len()
+            );
+        }
+        Ok(_) => {}
+        Err(err) => eprintln!("[Snapshots] Retention pruning failed: {err:#}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_snapshot_timestamp, plan_snapshot_prune};
+    use crate::types::{SnapshotFileInfo, SnapshotRetentionPolicy};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn snapshot_at(created_at: DateTime<Utc>) -> SnapshotFileInfo {
+        let file_name = format!("auth-snapshot-{}.json", created_at.format("%Y%m%dT%H%M%SZ"));
+        SnapshotFileInfo {
+            path: format!("/tmp/snapshots/{file_name}"),
+            file_name,
+            created_at,
+        }
+    }
+
+    fn names(snapshots: &[SnapshotFileInfo]) -> Vec<String> {
+        snapshots.iter().map(|s| s.file_name.clone()).collect()
+    }
+
+    #[test]
+    fn parses_timestamp_with_and_without_collision_suffix() {
+        let expected = Utc.with_ymd_and_hms(2026, 2, 27, 10, 30, 5).unwrap();
+        assert_eq!(
+            parse_snapshot_timestamp("auth-snapshot-20260227T103005Z.json"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_snapshot_timestamp("auth-snapshot-20260227T103005Z-2.json"),
+            Some(expected)
+        );
+        assert_eq!(parse_snapshot_timestamp("notes.json"), None);
+    }
+
+    #[test]
+    fn empty_policy_keeps_everything() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let snapshots = vec![
+            snapshot_at(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+            snapshot_at(now),
+        ];
+
+        let (kept, pruned) =
+            plan_snapshot_prune(snapshots, &SnapshotRetentionPolicy::default(), now);
+
+        assert_eq!(kept.len(), 2);
+        assert!(pruned.is_empty());
+    }
+
+    #[test]
+    fn keep_last_and_daily_buckets_are_combined() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 5, 12, 0, 0).unwrap();
+        let snapshots = vec![
+            snapshot_at(Utc.with_ymd_and_hms(2026, 3, 5, 11, 0, 0).unwrap()),
+            snapshot_at(Utc.with_ymd_and_hms(2026, 3, 5, 9, 0, 0).unwrap()),
+            snapshot_at(Utc.with_ymd_and_hms(2026, 3, 4, 18, 0, 0).unwrap()),
+            snapshot_at(Utc.with_ymd_and_hms(2026, 3, 4, 8, 0, 0).unwrap()),
+            snapshot_at(Utc.with_ymd_and_hms(2026, 3, 1, 8, 0, 0).unwrap()),
+        ];
+        let policy = SnapshotRetentionPolicy {
+            keep_last: Some(1),
+            keep_daily: Some(2),
+            ..Default::default()
+        };
+
+        let (kept, pruned) = plan_snapshot_prune(snapshots, &policy, now);
+
+        assert_eq!(
+            names(&kept),
+            vec![
+                "auth-snapshot-20260305T110000Z.json",
+                "auth-snapshot-20260304T180000Z.json",
+            ]
+        );
+        assert_eq!(pruned.len(), 3);
+    }
+
+    #[test]
+    fn max_age_overrides_keep_rules() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 0, 0, 0).unwrap();
+        let snapshots = vec![
+            snapshot_at(Utc.with_ymd_and_hms(2026, 3, 30, 0, 0, 0).unwrap()),
+            snapshot_at(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()),
+        ];
+        let policy = SnapshotRetentionPolicy {
+            keep_weekly: Some(10),
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+
+        let (kept, pruned) = plan_snapshot_prune(snapshots, &policy, now);
+
+        assert_eq!(names(&kept), vec!["auth-snapshot-20260330T000000Z.json"]);
+        assert_eq!(names(&pruned), vec!["auth-snapshot-20260101T000000Z.json"]);
+    }
+
+    #[test]
+    fn huge_max_age_means_no_limit() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 0, 0, 0).unwrap();
+        let snapshots = vec![snapshot_at(
+            Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(),
+        )];
+        let policy = SnapshotRetentionPolicy {
+            max_age_days: Some(u32::MAX),
+            ..Default::default()
+        };
+
+        let (kept, pruned) = plan_snapshot_prune(snapshots, &policy, now);
+
+        assert_eq!(kept.len(), 1);
+        assert!(pruned.is_empty());
+    }
+}
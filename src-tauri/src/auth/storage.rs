@@ -1,12 +1,23 @@
 //! Account storage module - manages reading and writing accounts.json
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{collections::HashMap, collections::HashSet};
 
 use anyhow::{Context, Result};
-
-use crate::types::{AccountsStore, AuthData, AuthMode, StoredAccount};
+use chrono::Utc;
+
+use crate::auth::activity::record_activation;
+use crate::auth::audit::record_audit;
+use crate::auth::identity::{
+    find_duplicate_account, group_duplicate_indexes, identity_match, is_same_identity,
+    merge_credentials_into,
+};
+use crate::auth::notes::merge_annotations;
+use crate::types::{
+    AccountInfo, AccountsStore, AppSettings, AuditAction, AuthData, AuthMode,
+    DuplicateAccountGroup, StoredAccount, TrashedAccount,
+};
 
 const LEGACY_KEYCHAIN_PLACEHOLDER: &str = "__stored_in_keychain__";
 
@@ -37,6 +48,36 @@ pub fn get_accounts_file() -> Result<PathBuf> {
     Ok(get_config_dir()?.join("accounts.json"))
 }
 
+/// Get the path to settings.json
+pub fn get_settings_file() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("settings.json"))
+}
+
+/// Get the path to switch-journal.json
+pub fn get_switch_journal_file() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("switch-journal.json"))
+}
+
+/// Get the path to token-revocations.json
+pub fn get_revocation_log_file() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("token-revocations.json"))
+}
+
+/// Get the path to activations.json
+pub fn get_activation_log_file() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("activations.json"))
+}
+
+/// Get the path to audit-log.jsonl
+pub fn get_audit_log_file() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("audit-log.jsonl"))
+}
+
+/// Get the path to pending-switch.json
+pub fn get_pending_switch_file() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("pending-switch.json"))
+}
+
 /// Get the path to the session snapshots directory
 pub fn get_snapshots_dir() -> Result<PathBuf> {
     Ok(get_config_dir()?.join("snapshots"))
@@ -76,6 +117,15 @@ pub fn load_accounts() -> Result<AccountsStore> {
     let mut store: AccountsStore = serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse accounts file: {}", path.display()))?;
 
+    // Records saved before organizations were stored get them from their id token
+    for account in store
+        .accounts
+        .iter_mut()
+        .filter(|account| account.organizations.is_empty())
+    {
+        account.refresh_organizations();
+    }
+
     let initial_len = store.accounts.len();
     let mut removed_names: Vec<String> = Vec::new();
 
@@ -122,20 +172,58 @@ pub fn save_accounts(store: &AccountsStore) -> Result<()> {
     let content =
         serde_json::to_string_pretty(store).context("Failed to serialize accounts store")?;
 
-    fs::write(&path, content)
+    write_private_file(&path, content.as_bytes())
         .with_context(|| format!("Failed to write accounts file: {}", path.display()))?;
 
+    record_activation(store.active_account_id.as_deref());
+    Ok(())
+}
+
+/// Write a file that may hold credentials or account metadata, restricted to the owner on Unix
+pub(crate) fn write_private_file(path: &Path, content: &[u8]) -> Result<()> {
+    fs::write(path, content)?;
+
     // Set restrictive permissions on Unix
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
         let perms = fs::Permissions::from_mode(0o600);
-        fs::set_permissions(&path, perms)?;
+        fs::set_permissions(path, perms)?;
     }
 
     Ok(())
 }
 
+/// Load app settings from disk, falling back to defaults when the file is missing
+pub fn load_settings() -> Result<AppSettings> {
+    let path = get_settings_file()?;
+
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read settings file: {}", path.display()))?;
+
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse settings file: {}", path.display()))
+}
+
+/// Save app settings to disk
+pub fn save_settings(settings: &AppSettings) -> Result<()> {
+    let path = get_settings_file()?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let content = serde_json::to_string_pretty(settings).context("Failed to serialize settings")?;
+
+    write_private_file(&path, content.as_bytes())
+        .with_context(|| format!("Failed to write settings file: {}", path.display()))
+}
+
 /// Add a new account to the store
 pub fn add_account(account: StoredAccount) -> Result<StoredAccount> {
     let mut store = load_accounts()?;
@@ -145,6 +233,14 @@ pub fn add_account(account: StoredAccount) -> Result<StoredAccount> {
         anyhow::bail!("An account with name '{}' already exists", account.name);
     }
 
+    // Check for the same login stored under another name
+    if let Some(existing) = find_duplicate_account(&store.accounts, &account) {
+        anyhow::bail!(
+            "This login is already stored as '{}'. Update that account's credentials instead.",
+            existing.name
+        );
+    }
+
     let account_clone = account.clone();
     store.accounts.push(account);
 
@@ -154,26 +250,156 @@ pub fn add_account(account: StoredAccount) -> Result<StoredAccount> {
     }
 
     save_accounts(&store)?;
+    record_audit(AuditAction::AccountAdded, Some(&account_clone), None);
     Ok(account_clone)
 }
 
-/// Remove an account by ID
-pub fn remove_account(account_id: &str) -> Result<()> {
+/// Update the credentials of the stored record holding the same login, or add the account.
+///
+/// Returns the stored record and whether it was an existing one.
+pub fn add_or_merge_account(account: StoredAccount) -> Result<(StoredAccount, bool)> {
     let mut store = load_accounts()?;
 
-    let initial_len = store.accounts.len();
-    store.accounts.retain(|a| a.id != account_id);
+    let Some(existing) = store
+        .accounts
+        .iter_mut()
+        .find(|a| is_same_identity(a, &account))
+    else {
+        return add_account(account).map(|stored| (stored, false));
+    };
 
-    if store.accounts.len() == initial_len {
-        anyhow::bail!("Account not found: {account_id}");
+    merge_credentials_into(existing, &account);
+    let updated = existing.clone();
+    save_accounts(&store)?;
+    record_audit(AuditAction::CredentialsUpdated, Some(&updated), None);
+    Ok((updated, true))
+}
+
+/// Find groups of stored records that hold the same login
+pub fn find_duplicate_account_groups() -> Result<Vec<DuplicateAccountGroup>> {
+    let store = load_accounts()?;
+    let active_id = store.active_account_id.as_deref();
+
+    Ok(group_duplicate_indexes(&store.accounts)
+        .into_iter()
+        .filter_map(|indexes| {
+            let first = &store.accounts[indexes[0]];
+            let matched_by = identity_match(first, &store.accounts[indexes[1]])?;
+            Some(DuplicateAccountGroup {
+                matched_by,
+                accounts: indexes
+                    .iter()
+                    .map(|&index| AccountInfo::from_stored(&store.accounts[index], active_id))
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+/// Merge duplicate records into a primary record, keeping its ID and name.
+///
+/// Credentials come from whichever record in the group was used or added most
+/// recently, since that one is most likely to hold a live refresh token.
+pub fn merge_duplicate_accounts(
+    primary_id: &str,
+    duplicate_ids: &[String],
+) -> Result<StoredAccount> {
+    let mut store = load_accounts()?;
+
+    let primary = store
+        .accounts
+        .iter()
+        .find(|a| a.id == primary_id)
+        .cloned()
+        .context("Account not found")?;
+
+    let mut group = vec![primary.clone()];
+    for duplicate_id in duplicate_ids {
+        if duplicate_id == primary_id {
+            continue;
+        }
+        let duplicate = store
+            .accounts
+            .iter()
+            .find(|a| a.id == *duplicate_id)
+            .with_context(|| format!("Account not found: {duplicate_id}"))?;
+        if !is_same_identity(&primary, duplicate) {
+            anyhow::bail!(
+                "'{}' does not hold the same login as '{}'",
+                duplicate.name,
+                primary.name
+            );
+        }
+        group.push(duplicate.clone());
+    }
+
+    let freshest = group
+        .iter()
+        .max_by_key(|a| a.last_used_at.unwrap_or(a.created_at))
+        .cloned()
+        .unwrap_or_else(|| primary.clone());
+    let last_used_at = group.iter().filter_map(|a| a.last_used_at).max();
+    let mut tags: Vec<String> = group.iter().flat_map(|a| a.tags.clone()).collect();
+    tags.sort();
+    tags.dedup();
+
+    store
+        .accounts
+        .retain(|a| a.id == primary_id || !duplicate_ids.contains(&a.id));
+
+    let merged = store
+        .accounts
+        .iter_mut()
+        .find(|a| a.id == primary_id)
+        .context("Account not found")?;
+    merge_credentials_into(merged, &freshest);
+    merged.last_used_at = last_used_at;
+    merged.tags = tags;
+    merge_annotations(merged, &group[1..]);
+    let merged = merged.clone();
+
+    if store
+        .active_account_id
+        .as_ref()
+        .is_some_and(|active_id| duplicate_ids.contains(active_id))
+    {
+        store.active_account_id = Some(merged.id.clone());
     }
 
+    save_accounts(&store)?;
+    record_audit(
+        AuditAction::AccountsMerged,
+        Some(&merged),
+        Some(format!("Merged duplicates: {}", duplicate_ids.join(", "))),
+    );
+    Ok(merged)
+}
+
+/// Remove an account by ID, moving it to the trash
+pub fn remove_account(account_id: &str) -> Result<()> {
+    let mut store = load_accounts()?;
+
+    let index = store
+        .accounts
+        .iter()
+        .position(|a| a.id == account_id)
+        .with_context(|| format!("Account not found: {account_id}"))?;
+
+    // Keep the record in the trash so it can be restored
+    let account = store.accounts.remove(index);
+    let deleted = account.clone();
+    store.trash.push(TrashedAccount {
+        account,
+        deleted_at: Utc::now(),
+    });
+
     // If we removed the active account, clear it or set to first available
     if store.active_account_id.as_deref() == Some(account_id) {
         store.active_account_id = store.accounts.first().map(|a| a.id.clone());
     }
 
     save_accounts(&store)?;
+    record_audit(AuditAction::AccountDeleted, Some(&deleted), None);
     Ok(())
 }
 
@@ -292,9 +518,12 @@ pub fn update_account_metadata(
         .find(|a| a.id == account_id)
         .context("Account not found")?;
 
-    if let Some(new_name) = name {
-        account.name = new_name;
-    }
+    let previous_name = match name {
+        Some(new_name) if new_name != account.name => {
+            Some(std::mem::replace(&mut account.name, new_name))
+        }
+        _ => None,
+    };
 
     if email.is_some() {
         account.email = email;
@@ -304,10 +533,49 @@ pub fn update_account_metadata(
         account.plan_type = plan_type;
     }
 
+    let updated = account.clone();
     save_accounts(&store)?;
+    if let Some(previous_name) = previous_name {
+        record_audit(
+            AuditAction::AccountRenamed,
+            Some(&updated),
+            Some(format!("Renamed from '{previous_name}'")),
+        );
+    }
     Ok(())
 }
 
+/// Choose which workspace's ID is written to auth.json for an account.
+///
+/// `None` restores the account ID from the token.
+pub fn set_account_workspace(
+    account_id: &str,
+    workspace_id: Option<String>,
+) -> Result<StoredAccount> {
+    let mut store = load_accounts()?;
+
+    let account = store
+        .accounts
+        .iter_mut()
+        .find(|a| a.id == account_id)
+        .context("Account not found")?;
+
+    if let Some(workspace_id) = workspace_id.as_deref() {
+        if !account.workspace_ids().contains(&workspace_id) {
+            anyhow::bail!(
+                "Workspace '{workspace_id}' is not a ChatGPT workspace of '{}'",
+                account.name
+            );
+        }
+    }
+
+    account.selected_workspace_id = workspace_id;
+
+    let updated = account.clone();
+    save_accounts(&store)?;
+    Ok(updated)
+}
+
 /// Replace OAuth credentials for an existing account without changing its ID/name
 pub fn replace_account_chatgpt_credentials(
     account_id: &str,
@@ -335,8 +603,10 @@ pub fn replace_account_chatgpt_credentials(
     };
     account.email = email;
     account.plan_type = plan_type;
+    account.refresh_organizations();
 
     let updated = account.clone();
     save_accounts(&store)?;
+    record_audit(AuditAction::AccountReconnected, Some(&updated), None);
     Ok(updated)
 }
@@ -6,13 +6,20 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 
-use crate::auth::storage::ensure_snapshots_dir;
+use crate::auth::audit::record_audit;
+use crate::auth::claims::parse_id_token;
+use crate::auth::journal::record_switch;
+use crate::auth::snapshots::apply_snapshot_retention;
+use crate::auth::storage::{
+    ensure_snapshots_dir, load_accounts, set_active_account, touch_account, write_private_file,
+};
 use crate::types::{
-    AuthData, AuthDotJson, AuthMode, CurrentAuthStatus, CurrentAuthSummary, StoredAccount,
-    TokenData,
+    AuditAction, AuthData, AuthDotJson, AuthMode, CurrentAuthStatus, CurrentAuthSummary,
+    PreviousAuthState, StoredAccount, TokenData,
 };
 
 const KEYCHAIN_PLACEHOLDER: &str = "__stored_in_keychain__";
+const PRE_SWITCH_SNAPSHOT_LABEL: &str = "pre-switch";
 
 fn is_placeholder_value(value: &str) -> bool {
     value.is_empty() || value == KEYCHAIN_PLACEHOLDER
@@ -22,7 +29,7 @@ fn has_non_empty_value(value: Option<&str>) -> bool {
     value.is_some_and(|item| !item.trim().is_empty())
 }
 
-fn account_has_usable_credentials(account: &StoredAccount) -> bool {
+pub(crate) fn account_has_usable_credentials(account: &StoredAccount) -> bool {
     match &account.auth_data {
         AuthData::ApiKey { key } => !is_placeholder_value(key),
         AuthData::ChatGPT {
@@ -55,6 +62,10 @@ pub fn get_codex_auth_file() -> Result<PathBuf> {
 }
 
 /// Switch to a specific account by writing its credentials to ~/.codex/auth.json
+///
+/// The outgoing auth.json is snapshotted first unless it matches a stored account,
+/// and the switch is recorded in the switch journal so it can be undone. Once
+/// auth.json is written the switch stands, so a failed journal write is only logged.
 pub fn switch_to_account(account: &StoredAccount) -> Result<()> {
     if !account_has_usable_credentials(account) {
         anyhow::bail!(
@@ -63,30 +74,115 @@ pub fn switch_to_account(account: &StoredAccount) -> Result<()> {
         );
     }
 
+    let store = load_accounts()?;
+    let previous_auth = capture_outgoing_auth(&store.accounts)?;
+
+    write_account_auth_json(account)?;
+
+    if let Err(err) = record_switch(
+        store.active_account_id.clone(),
+        account.id.clone(),
+        previous_auth,
+    ) {
+        eprintln!("[Switch] Failed to record switch in journal: {err:#}");
+    }
+
+    let previous_name = store
+        .active_account_id
+        .as_deref()
+        .filter(|id| *id != account.id)
+        .and_then(|id| store.accounts.iter().find(|a| a.id == id))
+        .map(|previous| format!("Switched from '{}'", previous.name));
+    record_audit(AuditAction::AccountSwitched, Some(account), previous_name);
+
+    Ok(())
+}
+
+/// Switch to a stored account by ID, then mark it active and update its last use
+pub fn activate_account(account_id: &str) -> Result<StoredAccount> {
+    let store = load_accounts()?;
+    let account = store
+        .accounts
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .with_context(|| format!("Account not found: {account_id}"))?;
+
+    switch_to_account(&account)?;
+    set_active_account(account_id)?;
+    touch_account(account_id)?;
+
+    Ok(account)
+}
+
+/// Write an account's credentials to auth.json without touching the switch journal
+pub(crate) fn write_account_auth_json(account: &StoredAccount) -> Result<()> {
+    let auth_json = create_auth_json(account)?;
+    let content =
+        serde_json::to_string_pretty(&auth_json).context("Failed to serialize auth.json")?;
+
+    write_auth_json_content(&content)
+}
+
+/// Replace auth.json with raw content, creating the Codex home if needed
+pub(crate) fn write_auth_json_content(content: &str) -> Result<()> {
     let codex_home = get_codex_home()?;
 
     // Ensure the codex home directory exists
     fs::create_dir_all(&codex_home)
         .with_context(|| format!("Failed to create codex home: {}", codex_home.display()))?;
 
-    let auth_json = create_auth_json(account)?;
-
     let auth_path = codex_home.join("auth.json");
-    let content =
-        serde_json::to_string_pretty(&auth_json).context("Failed to serialize auth.json")?;
+    write_private_file(&auth_path, content.as_bytes())
+        .with_context(|| format!("Failed to write auth.json: {}", auth_path.display()))
+}
 
-    fs::write(&auth_path, content)
-        .with_context(|| format!("Failed to write auth.json: {}", auth_path.display()))?;
+/// Find the stored account whose credentials are the ones in an auth.json
+pub(crate) fn find_account_matching_auth<'a>(
+    accounts: &'a [StoredAccount],
+    auth: &AuthDotJson,
+) -> Option<&'a StoredAccount> {
+    accounts.iter().find(|account| match &account.auth_data {
+        AuthData::ApiKey { key } => auth.openai_api_key.as_deref() == Some(key.as_str()),
+        AuthData::ChatGPT {
+            access_token,
+            refresh_token,
+            ..
+        } => auth.tokens.as_ref().is_some_and(|tokens| {
+            tokens.refresh_token == *refresh_token || tokens.access_token == *access_token
+        }),
+    })
+}
 
-    // Set restrictive permissions on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let perms = fs::Permissions::from_mode(0o600);
-        fs::set_permissions(&auth_path, perms)?;
+/// Record what auth.json holds before it gets overwritten.
+///
+/// Files that do not belong to a stored account (for example a fresh `codex login`)
+/// are preserved as a pre-switch snapshot.
+pub(crate) fn capture_outgoing_auth(accounts: &[StoredAccount]) -> Result<PreviousAuthState> {
+    let auth_path = get_codex_auth_file()?;
+    if !auth_path.exists() {
+        return Ok(PreviousAuthState::Missing);
     }
 
-    Ok(())
+    let content = fs::read_to_string(&auth_path)
+        .with_context(|| format!("Failed to read auth.json: {}", auth_path.display()))?;
+
+    if let Ok(auth) = serde_json::from_str::<AuthDotJson>(&content) {
+        if let Some(account) = find_account_matching_auth(accounts, &auth) {
+            return Ok(PreviousAuthState::StoredAccount {
+                account_id: account.id.clone(),
+            });
+        }
+    }
+
+    let snapshot_path = write_snapshot_file(&content, Some(PRE_SWITCH_SNAPSHOT_LABEL))?;
+    println!(
+        "[Switch] Saved unmatched auth.json as {}",
+        snapshot_path.display()
+    );
+
+    Ok(PreviousAuthState::Snapshot {
+        path: snapshot_path.display().to_string(),
+    })
 }
 
 /// Create an AuthDotJson structure from a StoredAccount
@@ -108,7 +204,13 @@ fn create_auth_json(account: &StoredAccount) -> Result<AuthDotJson> {
                 id_token: id_token.clone(),
                 access_token: access_token.clone(),
                 refresh_token: refresh_token.clone(),
-                account_id: account_id.clone(),
+                // Only a workspace the token still lists, never a platform organization
+                account_id: account
+                    .selected_workspace_id
+                    .as_deref()
+                    .filter(|id| account.workspace_ids().contains(id))
+                    .map(str::to_string)
+                    .or_else(|| account_id.clone()),
             }),
             last_refresh: Some(Utc::now()),
         }),
@@ -117,64 +219,45 @@ fn create_auth_json(account: &StoredAccount) -> Result<AuthDotJson> {
 
 /// Import an account from an existing auth.json file
 pub fn import_from_auth_json(path: &str, account_name: String) -> Result<StoredAccount> {
+    let auth = read_auth_json_file(path)?;
+    account_from_auth_json(auth, account_name)
+}
+
+/// Read and parse an auth.json-shaped file
+pub(crate) fn read_auth_json_file(path: &str) -> Result<AuthDotJson> {
     let content =
         fs::read_to_string(path).with_context(|| format!("Failed to read auth.json: {path}"))?;
 
-    let auth: AuthDotJson = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse auth.json: {path}"))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse auth.json: {path}"))
+}
 
+/// Build a new stored account from parsed auth.json content
+pub(crate) fn account_from_auth_json(
+    auth: AuthDotJson,
+    account_name: String,
+) -> Result<StoredAccount> {
     // Determine auth mode and create account
     if let Some(api_key) = auth.openai_api_key {
         Ok(StoredAccount::new_api_key(account_name, api_key))
     } else if let Some(tokens) = auth.tokens {
-        // Try to extract email and plan from id_token
-        let (email, plan_type) = parse_id_token_claims(&tokens.id_token);
+        // Try to extract email, plan and account ID from id_token
+        let claims = parse_id_token(&tokens.id_token).unwrap_or_default();
+        let account_id = tokens.account_id.or_else(|| claims.account_id());
 
         Ok(StoredAccount::new_chatgpt(
             account_name,
-            email,
-            plan_type,
+            claims.email(),
+            claims.plan_type(),
             tokens.id_token,
             tokens.access_token,
             tokens.refresh_token,
-            tokens.account_id,
+            account_id,
         ))
     } else {
         anyhow::bail!("auth.json contains neither API key nor tokens");
     }
 }
 
-/// Parse claims from a JWT ID token (without validation)
-fn parse_id_token_claims(id_token: &str) -> (Option<String>, Option<String>) {
-    let parts: Vec<&str> = id_token.split('.').collect();
-    if parts.len() != 3 {
-        return (None, None);
-    }
-
-    // Decode the payload (second part)
-    let payload =
-        match base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, parts[1]) {
-            Ok(bytes) => bytes,
-            Err(_) => return (None, None),
-        };
-
-    let json: serde_json::Value = match serde_json::from_slice(&payload) {
-        Ok(v) => v,
-        Err(_) => return (None, None),
-    };
-
-    let email = json.get("email").and_then(|v| v.as_str()).map(String::from);
-
-    // Look for plan type in the OpenAI auth claims
-    let plan_type = json
-        .get("https://api.openai.com/auth")
-        .and_then(|auth| auth.get("chatgpt_plan_type"))
-        .and_then(|v| v.as_str())
-        .map(String::from);
-
-    (email, plan_type)
-}
-
 /// Read the current auth.json file if it exists
 pub fn read_current_auth() -> Result<Option<AuthDotJson>> {
     let path = get_codex_auth_file()?;
@@ -196,12 +279,17 @@ fn to_iso_utc(time: std::time::SystemTime) -> Option<DateTime<Utc>> {
     Some(DateTime::<Utc>::from(time))
 }
 
-pub(crate) fn build_snapshot_filename(now: DateTime<Utc>, collision_index: u32) -> String {
+pub(crate) fn build_snapshot_filename(
+    now: DateTime<Utc>,
+    label: Option<&str>,
+    collision_index: u32,
+) -> String {
     let timestamp = now.format("%Y%m%dT%H%M%SZ");
+    let label = label.map(|label| format!("-{label}")).unwrap_or_default();
     if collision_index == 0 {
-        format!("auth-snapshot-{timestamp}.json")
+        format!("auth-snapshot-{timestamp}{label}.json")
     } else {
-        format!("auth-snapshot-{timestamp}-{collision_index}.json")
+        format!("auth-snapshot-{timestamp}{label}-{collision_index}.json")
     }
 }
 
@@ -241,12 +329,12 @@ pub(crate) fn derive_summary_from_auth(
             };
         }
 
-        let (email, plan_type) = parse_id_token_claims(&tokens.id_token);
+        let claims = parse_id_token(&tokens.id_token).unwrap_or_default();
         return CurrentAuthSummary {
             status: CurrentAuthStatus::Ready,
             auth_mode: Some(AuthMode::ChatGPT),
-            email,
-            plan_type,
+            email: claims.email(),
+            plan_type: claims.plan_type(),
             auth_file_path,
             snapshots_dir_path,
             last_modified_at,
@@ -338,11 +426,20 @@ pub fn create_auth_snapshot_file() -> Result<String> {
     serde_json::from_str::<AuthDotJson>(&auth_content)
         .with_context(|| format!("Failed to parse auth.json: {}", auth_path.display()))?;
 
+    let snapshot_path = write_snapshot_file(&auth_content, None)?;
+
+    apply_snapshot_retention();
+
+    Ok(snapshot_path.display().to_string())
+}
+
+/// Write auth.json content to a new, uniquely named file in the snapshots directory
+fn write_snapshot_file(auth_content: &str, label: Option<&str>) -> Result<PathBuf> {
     let snapshots_dir = ensure_snapshots_dir()?;
     let now = Utc::now();
 
     for collision_index in 0..1000 {
-        let filename = build_snapshot_filename(now, collision_index);
+        let filename = build_snapshot_filename(now, label, collision_index);
         let snapshot_path = snapshots_dir.join(filename);
 
         match std::fs::OpenOptions::new()
@@ -368,7 +465,12 @@ pub fn create_auth_snapshot_file() -> Result<String> {
                         })?;
                 }
 
-                return Ok(snapshot_path.display().to_string());
+                let file_name = snapshot_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned());
+                record_audit(AuditAction::SnapshotCreated, None, file_name);
+
+                return Ok(snapshot_path);
             }
             Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
             Err(err) => {
@@ -398,7 +500,11 @@ pub fn has_active_login() -> Result<bool> {
 
 #[cfg(test)]
 mod tests {
-    use super::{account_has_usable_credentials, StoredAccount};
+    use super::{
+        account_has_usable_credentials, create_auth_json, find_account_matching_auth, StoredAccount,
+    };
+    use crate::types::{AuthDotJson, TokenData};
+    use base64::Engine;
 
     #[test]
     fn rejects_placeholder_chatgpt_credentials() {
@@ -431,4 +537,122 @@ mod tests {
 
         assert!(account_has_usable_credentials(&account));
     }
+
+    #[test]
+    fn matches_auth_json_to_stored_account_by_tokens_or_api_key() {
+        let chatgpt = StoredAccount::new_chatgpt(
+            "Work".to_string(),
+            None,
+            None,
+            "id-real".to_string(),
+            "access-real".to_string(),
+            "refresh-real".to_string(),
+            None,
+        );
+        let api = StoredAccount::new_api_key("Api".to_string(), "sk-test".to_string());
+        let accounts = vec![chatgpt.clone(), api.clone()];
+
+        let refreshed = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(TokenData {
+                id_token: "id-new".to_string(),
+                access_token: "access-new".to_string(),
+                refresh_token: "refresh-real".to_string(),
+                account_id: None,
+            }),
+            last_refresh: None,
+        };
+        let api_auth = AuthDotJson {
+            openai_api_key: Some("sk-test".to_string()),
+            tokens: None,
+            last_refresh: None,
+        };
+        let fresh_login = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(TokenData {
+                id_token: "id-other".to_string(),
+                access_token: "access-other".to_string(),
+                refresh_token: "refresh-other".to_string(),
+                account_id: None,
+            }),
+            last_refresh: None,
+        };
+
+        assert_eq!(
+            find_account_matching_auth(&accounts, &refreshed).map(|a| a.id.as_str()),
+            Some(chatgpt.id.as_str())
+        );
+        assert_eq!(
+            find_account_matching_auth(&accounts, &api_auth).map(|a| a.id.as_str()),
+            Some(api.id.as_str())
+        );
+        assert!(find_account_matching_auth(&accounts, &fresh_login).is_none());
+    }
+
+    #[test]
+    fn writes_the_selected_workspace_to_auth_json() {
+        let payload = serde_json::json!({
+            "email": "dev@example.com",
+            "https://api.openai.com/auth": {
+                "chatgpt_account_id": "personal-workspace",
+                "organizations": [
+                    { "id": "org-personal", "is_default": true },
+                    { "id": "team-workspace", "title": "Acme" }
+                ]
+            }
+        });
+        let id_token = format!(
+            "header.{}.signature",
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(payload.to_string())
+        );
+        let mut account = StoredAccount::new_chatgpt(
+            "Work".to_string(),
+            Some("dev@example.com".to_string()),
+            None,
+            id_token,
+            "access".to_string(),
+            "refresh".to_string(),
+            Some("personal-workspace".to_string()),
+        );
+
+        assert_eq!(account.organizations.len(), 2);
+        assert_eq!(
+            account.workspace_ids(),
+            ["personal-workspace", "team-workspace"]
+        );
+        let account_id = |account: &StoredAccount| {
+            create_auth_json(account)
+                .expect("auth.json")
+                .tokens
+                .and_then(|tokens| tokens.account_id)
+        };
+        assert_eq!(account_id(&account).as_deref(), Some("personal-workspace"));
+
+        account.selected_workspace_id = Some("team-workspace".to_string());
+        assert_eq!(account_id(&account).as_deref(), Some("team-workspace"));
+
+        // A platform organization is never written, even if it was stored earlier
+        account.selected_workspace_id = Some("org-personal".to_string());
+        assert_eq!(account_id(&account).as_deref(), Some("personal-workspace"));
+
+        // Dropped once a refreshed token no longer lists the workspace
+        account.selected_workspace_id = Some("org-gone".to_string());
+        account.refresh_organizations();
+        assert_eq!(account.selected_workspace_id, None);
+    }
+
+    #[test]
+    fn keeps_notes_and_metadata_out_of_auth_json() {
+        let mut account = StoredAccount::new_api_key("CI".to_string(), "sk-ci".to_string());
+        account.notes = Some("billing owner: finance".to_string());
+        account
+            .metadata
+            .insert("cost center".to_string(), "cc-42".to_string());
+
+        let content = serde_json::to_string(&create_auth_json(&account).expect("auth.json"))
+            .expect("serialize auth.json");
+
+        assert!(!content.contains("finance"));
+        assert!(!content.contains("cc-42"));
+    }
 }
@@ -0,0 +1,177 @@
+//! Account tags - free-form labels for grouping accounts
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+use crate::auth::storage::{load_accounts, save_accounts};
+use crate::types::{AccountTagSummary, StoredAccount};
+
+const MAX_TAG_LENGTH: usize = 32;
+
+/// Trim and lowercase a tag, rejecting empty or overlong ones
+pub(crate) fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim().to_lowercase();
+
+    if tag.is_empty() {
+        anyhow::bail!("Tags cannot be empty");
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        anyhow::bail!("Tag '{tag}' is longer than {MAX_TAG_LENGTH} characters");
+    }
+    if tag.contains(',') {
+        anyhow::bail!("Tag '{tag}' cannot contain a comma");
+    }
+
+    Ok(tag)
+}
+
+/// Normalize a list of tags, dropping duplicates and sorting it
+pub(crate) fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Result<Vec<String>> {
+    let mut normalized = tags
+        .iter()
+        .map(|tag| normalize_tag(tag.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
+}
+
+/// Whether an account carries `tag`, compared case-insensitively
+pub fn account_has_tag(account: &StoredAccount, tag: &str) -> bool {
+    let tag = tag.trim().to_lowercase();
+    account.tags.contains(&tag)
+}
+
+/// Replace tags in place: every tag in `from` becomes `into`.
+///
+/// Returns how many accounts changed.
+pub(crate) fn retag_accounts<'a>(
+    accounts: impl IntoIterator<Item = &'a mut StoredAccount>,
+    from: &[String],
+    into: &str,
+) -> usize {
+    let mut changed = 0;
+
+    for account in accounts {
+        if !account.tags.iter().any(|tag| from.contains(tag)) {
+            continue;
+        }
+        account.tags.retain(|tag| !from.contains(tag));
+        account.tags.push(into.to_string());
+        account.tags.sort();
+        account.tags.dedup();
+        changed += 1;
+    }
+
+    changed
+}
+
+/// Replace the tags of one account
+pub fn set_account_tags(account_id: &str, tags: &[String]) -> Result<StoredAccount> {
+    let tags = normalize_tags(tags)?;
+    let mut store = load_accounts()?;
+
+    let account = store
+        .accounts
+        .iter_mut()
+        .find(|a| a.id == account_id)
+        .context("Account not found")?;
+    account.tags = tags;
+
+    let updated = account.clone();
+    save_accounts(&store)?;
+    Ok(updated)
+}
+
+/// All tags in use with their account counts, sorted by tag
+pub fn list_account_tags() -> Result<Vec<AccountTagSummary>> {
+    let store = load_accounts()?;
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for tag in store.accounts.iter().flat_map(|account| &account.tags) {
+        *counts.entry(tag).or_default() += 1;
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|(tag, account_count)| AccountTagSummary {
+            tag: tag.to_string(),
+            account_count,
+        })
+        .collect())
+}
+
+/// Rename a tag on every account; renaming onto an existing tag merges the two.
+///
+/// Returns how many accounts changed.
+pub fn rename_tag(from: &str, to: &str) -> Result<usize> {
+    merge_tags(&[from.to_string()], to)
+}
+
+/// Fold several tags into one across all accounts; returns how many accounts changed.
+///
+/// Trashed accounts are retagged too, so a restore does not bring an old tag back.
+pub fn merge_tags(sources: &[String], into: &str) -> Result<usize> {
+    let into = normalize_tag(into)?;
+    let sources: Vec<String> = normalize_tags(sources)?
+        .into_iter()
+        .filter(|tag| *tag != into)
+        .collect();
+    if sources.is_empty() {
+        return Ok(0);
+    }
+
+    let mut store = load_accounts()?;
+    let changed = retag_accounts(&mut store.accounts, &sources, &into);
+    let trashed = store.trash.iter_mut().map(|trashed| &mut trashed.account);
+    let trash_changed = retag_accounts(trashed, &sources, &into);
+    if changed > 0 || trash_changed > 0 {
+        save_accounts(&store)?;
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_has_tag, normalize_tags, retag_accounts};
+    use crate::types::StoredAccount;
+
+    fn tagged(name: &str, tags: &[&str]) -> StoredAccount {
+        let mut account = StoredAccount::new_api_key(name.to_string(), format!("sk-{name}"));
+        account.tags = normalize_tags(tags).unwrap();
+        account
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        assert_eq!(
+            normalize_tags(&[" Team-A ", "pro", "team-a"]).unwrap(),
+            ["pro", "team-a"]
+        );
+        assert!(normalize_tags(&["  "]).is_err());
+        assert!(normalize_tags(&["a,b"]).is_err());
+        assert!(account_has_tag(&tagged("x", &["pro"]), " PRO"));
+    }
+
+    #[test]
+    fn merges_tags_across_accounts() {
+        let mut accounts = vec![
+            tagged("a", &["team-a", "pro"]),
+            tagged("b", &["teama", "team-a"]),
+            tagged("c", &["personal"]),
+        ];
+
+        let changed = retag_accounts(
+            &mut accounts,
+            &["team-a".to_string(), "teama".to_string()],
+            "team-alpha",
+        );
+
+        assert_eq!(changed, 2);
+        assert_eq!(accounts[0].tags, ["pro", "team-alpha"]);
+        assert_eq!(accounts[1].tags, ["team-alpha"]);
+        assert_eq!(accounts[2].tags, ["personal"]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Token inspection - decodes stored JWT claims to judge credential health offline
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::auth::claims::{parse_access_token, parse_id_token};
+use crate::types::{AccountTokenClaims, AuthData, CredentialHealth, StoredAccount};
+
+/// Access tokens expiring within this window are reported as expiring soon
+const EXPIRING_SOON_WINDOW: Duration = Duration::hours(24);
+/// An access token expired this long ago means Codex has not refreshed the login for
+/// weeks, and the refresh token is most likely no longer accepted
+const STALE_REFRESH_WINDOW: Duration = Duration::days(30);
+
+/// Decode the id and access token claims of a ChatGPT account
+pub fn decode_account_token_claims(account: &StoredAccount) -> Option<AccountTokenClaims> {
+    let AuthData::ChatGPT {
+        id_token,
+        access_token,
+        ..
+    } = &account.auth_data
+    else {
+        return None;
+    };
+
+    let id_claims = parse_id_token(id_token);
+    let access_claims = parse_access_token(access_token);
+    if id_claims.is_none() && access_claims.is_none() {
+        return None;
+    }
+
+    let id_claims = id_claims.unwrap_or_default();
+    let access_claims = access_claims.unwrap_or_default();
+
+    Some(AccountTokenClaims {
+        subject: id_claims.sub.clone(),
+        plan_type: id_claims.plan_type().or_else(|| access_claims.plan_type()),
+        organizations: id_claims.organizations(),
+        id_token_issued_at: id_claims.issued_at(),
+        id_token_expires_at: id_claims.expires_at(),
+        access_token_issued_at: access_claims.issued_at(),
+        access_token_expires_at: access_claims.expires_at(),
+    })
+}
+
+/// Judge whether an account's credentials still work, from the access token expiry.
+///
+/// Codex refreshes an expired access token with the refresh token, so an expired
+/// account is only reported unusable when the refresh token is missing or the
+/// login has gone unrefreshed for longer than `STALE_REFRESH_WINDOW`.
+pub fn credential_health(
+    account: &StoredAccount,
+    claims: Option<&AccountTokenClaims>,
+    now: DateTime<Utc>,
+) -> CredentialHealth {
+    let AuthData::ChatGPT { refresh_token, .. } = &account.auth_data else {
+        return CredentialHealth::Valid;
+    };
+
+    let Some(expires_at) = claims.and_then(|claims| claims.access_token_expires_at) else {
+        return CredentialHealth::Unknown;
+    };
+
+    if expires_at <= now {
+        if refresh_token.trim().is_empty() || now - expires_at > STALE_REFRESH_WINDOW {
+            CredentialHealth::Expired
+        } else {
+            CredentialHealth::RefreshRequired
+        }
+    } else if expires_at - now <= EXPIRING_SOON_WINDOW {
+        CredentialHealth::ExpiringSoon
+    } else {
+        CredentialHealth::Valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{credential_health, decode_account_token_claims};
+    use crate::types::{CredentialHealth, StoredAccount};
+    use base64::Engine;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn jwt(payload: serde_json::Value) -> String {
+        let encode = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        format!(
+            "{}.{}.signature",
+            encode(br#"{"alg":"RS256"}"#),
+            encode(payload.to_string().as_bytes())
+        )
+    }
+
+    fn account(access_exp: i64, refresh_token: &str) -> StoredAccount {
+        let id_token = jwt(serde_json::json!({
+            "sub": "auth0|user-1",
+            "iat": 1_700_000_000,
+            "exp": 1_700_003_600,
+            "https://api.openai.com/auth": {
+                "chatgpt_plan_type": "team",
+                "organizations": [
+                    { "id": "org-personal", "title": "Personal", "role": "owner", "is_default": true },
+                    { "id": "org-team", "title": "Acme", "role": "member" }
+                ]
+            }
+        }));
+        let access_token = jwt(serde_json::json!({ "iat": 1_700_000_000, "exp": access_exp }));
+
+        StoredAccount::new_chatgpt(
+            "Work".to_string(),
+            Some("me@example.com".to_string()),
+            Some("team".to_string()),
+            id_token,
+            access_token,
+            refresh_token.to_string(),
+            None,
+        )
+    }
+
+    #[test]
+    fn decodes_id_and_access_token_claims() {
+        let claims =
+            decode_account_token_claims(&account(1_700_864_000, "refresh")).expect("claims");
+
+        assert_eq!(claims.subject.as_deref(), Some("auth0|user-1"));
+        assert_eq!(claims.plan_type.as_deref(), Some("team"));
+        assert_eq!(claims.organizations.len(), 2);
+        assert!(claims.organizations[0].is_default);
+        assert_eq!(claims.organizations[1].title.as_deref(), Some("Acme"));
+        assert_eq!(
+            claims.id_token_expires_at,
+            Some(Utc.timestamp_opt(1_700_003_600, 0).unwrap())
+        );
+        assert_eq!(
+            claims.access_token_expires_at,
+            Some(Utc.timestamp_opt(1_700_864_000, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn derives_health_from_access_token_expiry() {
+        let expires_at = Utc.timestamp_opt(1_700_864_000, 0).unwrap();
+        let health = |account: &StoredAccount, now| {
+            credential_health(account, decode_account_token_claims(account).as_ref(), now)
+        };
+        let with_refresh = account(expires_at.timestamp(), "refresh");
+        let without_refresh = account(expires_at.timestamp(), "");
+
+        assert_eq!(
+            health(&with_refresh, expires_at - Duration::days(3)),
+            CredentialHealth::Valid
+        );
+        assert_eq!(
+            health(&with_refresh, expires_at - Duration::hours(2)),
+            CredentialHealth::ExpiringSoon
+        );
+        assert_eq!(
+            health(&with_refresh, expires_at + Duration::minutes(1)),
+            CredentialHealth::RefreshRequired
+        );
+        assert_eq!(
+            health(&without_refresh, expires_at + Duration::minutes(1)),
+            CredentialHealth::Expired
+        );
+        assert_eq!(
+            health(&with_refresh, expires_at + Duration::days(31)),
+            CredentialHealth::Expired
+        );
+    }
+}
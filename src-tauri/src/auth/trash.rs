@@ -0,0 +1,196 @@
+//! Account trash - deleted accounts kept for a while so they can be restored
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::auth::audit::record_audit;
+use crate::auth::identity::find_duplicate_account;
+use crate::auth::storage::{load_accounts, load_settings, save_accounts, save_settings};
+use crate::types::{AuditAction, StoredAccount, TrashSettings, TrashedAccount, TrashedAccountInfo};
+
+/// Longest purge delay that can be configured, about ten years
+pub const MAX_PURGE_AFTER_DAYS: u32 = 3650;
+
+/// When a trashed account is purged; `None` when never, including past the end of time
+fn purge_at(trashed: &TrashedAccount, purge_after_days: Option<u32>) -> Option<DateTime<Utc>> {
+    let days = Duration::try_days(i64::from(purge_after_days?))?;
+    trashed.deleted_at.checked_add_signed(days)
+}
+
+/// Drop trashed accounts older than `purge_after_days`, returning them
+pub(crate) fn take_expired(
+    trash: &mut Vec<TrashedAccount>,
+    purge_after_days: Option<u32>,
+    now: DateTime<Utc>,
+) -> Vec<TrashedAccount> {
+    let (expired, kept) = trash.drain(..).partition(|trashed| {
+        purge_at(trashed, purge_after_days).is_some_and(|purge_at| purge_at <= now)
+    });
+    *trash = kept;
+    expired
+}
+
+/// Get the trash settings
+pub fn get_trash_settings() -> Result<TrashSettings> {
+    Ok(load_settings()?.trash)
+}
+
+/// Persist new trash settings
+pub fn set_trash_settings(trash: TrashSettings) -> Result<()> {
+    match trash.purge_after_days {
+        Some(0) => anyhow::bail!(
+            "Purge delay must be at least 1 day; leave it empty to keep deleted accounts until purged"
+        ),
+        Some(days) if days > MAX_PURGE_AFTER_DAYS => anyhow::bail!(
+            "Purge delay must be at most {MAX_PURGE_AFTER_DAYS} days; leave it empty to keep deleted accounts until purged"
+        ),
+        _ => {}
+    }
+
+    let mut settings = load_settings()?;
+    settings.trash = trash;
+    save_settings(&settings)
+}
+
+/// Purge trashed accounts past the configured delay; returns how many were removed
+pub fn purge_expired_trash() -> Result<usize> {
+    let purge_after_days = get_trash_settings()?.purge_after_days;
+    let mut store = load_accounts()?;
+
+    let expired = take_expired(&mut store.trash, purge_after_days, Utc::now());
+    if !expired.is_empty() {
+        save_accounts(&store)?;
+        for trashed in &expired {
+            record_audit(
+                AuditAction::AccountPurged,
+                Some(&trashed.account),
+                Some("Purged automatically".to_string()),
+            );
+        }
+        println!(
+            "[Trash] Purged {} account(s) deleted more than {} day(s) ago",
+            expired.len(),
+            purge_after_days.unwrap_or_default()
+        );
+    }
+
+    Ok(expired.len())
+}
+
+/// List trashed accounts, most recently deleted first
+pub fn list_trash() -> Result<Vec<TrashedAccountInfo>> {
+    let purge_after_days = get_trash_settings()?.purge_after_days;
+    let store = load_accounts()?;
+
+    let mut trash: Vec<TrashedAccountInfo> = store
+        .trash
+        .iter()
+        .map(|trashed| TrashedAccountInfo {
+            id: trashed.account.id.clone(),
+            name: trashed.account.name.clone(),
+            email: trashed.account.email.clone(),
+            plan_type: trashed.account.plan_type.clone(),
+            auth_mode: trashed.account.auth_mode,
+            deleted_at: trashed.deleted_at,
+            purge_at: purge_at(trashed, purge_after_days),
+        })
+        .collect();
+    trash.sort_by_key(|info| std::cmp::Reverse(info.deleted_at));
+
+    Ok(trash)
+}
+
+/// Move a trashed account back into the account list
+pub fn restore_account(account_id: &str) -> Result<StoredAccount> {
+    let mut store = load_accounts()?;
+
+    let index = store
+        .trash
+        .iter()
+        .position(|trashed| trashed.account.id == account_id)
+        .with_context(|| format!("Account not found in trash: {account_id}"))?;
+    let account = &store.trash[index].account;
+
+    if store.accounts.iter().any(|a| a.name == account.name) {
+        anyhow::bail!(
+            "An account named '{}' already exists. Rename it before restoring.",
+            account.name
+        );
+    }
+    if let Some(existing) = find_duplicate_account(&store.accounts, account) {
+        anyhow::bail!(
+            "This login is already stored as '{}'. Delete that account before restoring.",
+            existing.name
+        );
+    }
+
+    let account = store.trash.remove(index).account;
+    store.accounts.push(account.clone());
+    if store.active_account_id.is_none() {
+        store.active_account_id = Some(account.id.clone());
+    }
+
+    save_accounts(&store)?;
+    record_audit(AuditAction::AccountRestored, Some(&account), None);
+    Ok(account)
+}
+
+/// Permanently delete one trashed account, or the whole trash when `account_id` is `None`.
+///
+/// Returns how many accounts were purged.
+pub fn purge_trash(account_id: Option<&str>) -> Result<usize> {
+    let mut store = load_accounts()?;
+
+    let (purged, kept): (Vec<TrashedAccount>, Vec<TrashedAccount>) = store
+        .trash
+        .drain(..)
+        .partition(|trashed| account_id.is_none_or(|id| trashed.account.id == id));
+    store.trash = kept;
+
+    if let (Some(account_id), true) = (account_id, purged.is_empty()) {
+        anyhow::bail!("Account not found in trash: {account_id}");
+    }
+
+    save_accounts(&store)?;
+    for trashed in &purged {
+        record_audit(AuditAction::AccountPurged, Some(&trashed.account), None);
+    }
+    Ok(purged.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::take_expired;
+    use crate::types::{StoredAccount, TrashedAccount};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn trashed(name: &str, days_ago: i64) -> TrashedAccount {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        TrashedAccount {
+            account: StoredAccount::new_api_key(name.to_string(), "sk-test".to_string()),
+            deleted_at: now - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn purges_only_accounts_past_the_delay() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let mut trash = vec![trashed("old", 31), trashed("edge", 30), trashed("new", 2)];
+
+        let expired = take_expired(&mut trash, Some(30), now);
+
+        let names = |accounts: &[TrashedAccount]| {
+            accounts
+                .iter()
+                .map(|trashed| trashed.account.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&expired), ["old", "edge"]);
+        assert_eq!(names(&trash), ["new"]);
+
+        // Without a delay nothing is purged automatically
+        assert!(take_expired(&mut trash, None, now + Duration::days(365)).is_empty());
+        // A delay past the end of time never purges, and does not panic
+        assert!(take_expired(&mut trash, Some(u32::MAX), now).is_empty());
+    }
+}
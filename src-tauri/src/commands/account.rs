@@ -1,21 +1,38 @@
 //! Account management Tauri commands
 
+use crate::api::{forget_usage, latest_usage};
+use crate::auth::switcher::write_account_auth_json;
 use crate::auth::{
-    add_account, get_active_account, import_from_auth_json, load_accounts, remove_account,
-    reorder_accounts as reorder_stored_accounts, set_active_account, switch_to_account,
-    touch_account,
+    account_has_tag, activate_account, add_account, add_or_merge_account, build_account_stats,
+    find_duplicate_account, find_duplicate_account_groups, get_account, get_active_account,
+    import_accounts_from_directory, import_from_auth_json, list_account_tags as list_stored_tags,
+    load_accounts, load_activation_log, load_revocation_log, load_switch_journal,
+    merge_duplicate_accounts as merge_stored_duplicates, merge_tags, purge_trash,
+    query_accounts as query_stored_accounts, record_revocation, remove_account, rename_tag,
+    reorder_accounts as reorder_stored_accounts, revoke_account_tokens,
+    search_accounts as search_stored_accounts,
+    set_account_metadata_field as set_stored_metadata_field,
+    set_account_notes as set_stored_account_notes, set_account_tags as set_stored_account_tags,
+    set_account_workspace as set_stored_account_workspace,
+    undo_last_switch as undo_last_stored_switch, DEFAULT_STATS_WEEKS, DEFAULT_UNUSED_DAYS,
+};
+use crate::process::supersede_pending_switch;
+use crate::types::{
+    AccountDeletionReport, AccountInfo, AccountQuery, AccountQueryMatch, AccountTagSummary,
+    AccountUsageStats, ActivationRecord, BulkImportReport, DuplicateAccountGroup,
+    DuplicateStrategy, SwitchJournalEntry, SwitchUndoResult, TokenRevocationReport,
 };
-use crate::types::AccountInfo;
 
 /// List all accounts with their info
 #[tauri::command]
-pub async fn list_accounts() -> Result<Vec<AccountInfo>, String> {
+pub async fn list_accounts(tag: Option<String>) -> Result<Vec<AccountInfo>, String> {
     let store = load_accounts().map_err(|e| e.to_string())?;
     let active_id = store.active_account_id.as_deref();
 
     let accounts: Vec<AccountInfo> = store
         .accounts
         .iter()
+        .filter(|a| tag.as_deref().is_none_or(|tag| account_has_tag(a, tag)))
         .map(|a| AccountInfo::from_stored(a, active_id))
         .collect();
 
@@ -35,14 +52,27 @@ pub async fn get_active_account_info() -> Result<Option<AccountInfo>, String> {
     }
 }
 
-/// Add an account from an auth.json file
+/// Add an account from an auth.json file.
+///
+/// When the file holds a login that is already stored, the import fails unless
+/// `update_existing` is set, in which case that account's credentials are updated.
 #[tauri::command]
-pub async fn add_account_from_file(path: String, name: String) -> Result<AccountInfo, String> {
+pub async fn add_account_from_file(
+    path: String,
+    name: String,
+    update_existing: Option<bool>,
+) -> Result<AccountInfo, String> {
     // Import from the file
     let account = import_from_auth_json(&path, name).map_err(|e| e.to_string())?;
 
     // Add to storage
-    let stored = add_account(account).map_err(|e| e.to_string())?;
+    let stored = if update_existing.unwrap_or(false) {
+        add_or_merge_account(account)
+            .map(|(stored, _)| stored)
+            .map_err(|e| e.to_string())?
+    } else {
+        add_account(account).map_err(|e| e.to_string())?
+    };
 
     let store = load_accounts().map_err(|e| e.to_string())?;
     let active_id = store.active_account_id.as_deref();
@@ -50,35 +80,173 @@ pub async fn add_account_from_file(path: String, name: String) -> Result<Account
     Ok(AccountInfo::from_stored(&stored, active_id))
 }
 
-/// Switch to a different account
+/// Find the stored account that already holds the login in an auth.json file
 #[tauri::command]
-pub async fn switch_account(account_id: String) -> Result<(), String> {
+pub async fn check_import_duplicate(path: String) -> Result<Option<AccountInfo>, String> {
+    let candidate = import_from_auth_json(&path, String::new()).map_err(|e| e.to_string())?;
+
     let store = load_accounts().map_err(|e| e.to_string())?;
+    let active_id = store.active_account_id.as_deref();
 
-    // Find the account
-    let account = store
-        .accounts
+    Ok(find_duplicate_account(&store.accounts, &candidate)
+        .map(|existing| AccountInfo::from_stored(existing, active_id)))
+}
+
+/// Find groups of stored accounts that hold the same login
+#[tauri::command]
+pub async fn find_duplicate_accounts() -> Result<Vec<DuplicateAccountGroup>, String> {
+    find_duplicate_account_groups().map_err(|e| e.to_string())
+}
+
+/// Merge duplicate accounts into one, keeping the primary account's ID and name
+#[tauri::command]
+pub async fn merge_duplicate_accounts(
+    primary_account_id: String,
+    duplicate_account_ids: Vec<String>,
+) -> Result<AccountInfo, String> {
+    let merged = merge_stored_duplicates(&primary_account_id, &duplicate_account_ids)
+        .map_err(|e| e.to_string())?;
+    for duplicate_id in duplicate_account_ids
         .iter()
-        .find(|a| a.id == account_id)
-        .ok_or_else(|| format!("Account not found: {account_id}"))?;
+        .filter(|id| **id != primary_account_id)
+    {
+        forget_usage(duplicate_id);
+    }
 
-    // Write to ~/.codex/auth.json
-    switch_to_account(account).map_err(|e| e.to_string())?;
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    let active_id = store.active_account_id.as_deref();
 
-    // Update the active account in our store
-    set_active_account(&account_id).map_err(|e| e.to_string())?;
+    Ok(AccountInfo::from_stored(&merged, active_id))
+}
 
-    // Update last_used_at
-    touch_account(&account_id).map_err(|e| e.to_string())?;
+/// Import every auth.json-shaped file in a directory as accounts
+#[tauri::command]
+pub async fn import_accounts_from_dir(
+    path: String,
+    on_duplicate: Option<DuplicateStrategy>,
+) -> Result<BulkImportReport, String> {
+    import_accounts_from_directory(&path, on_duplicate.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
 
+/// Drop the queued switch before a manual one.
+///
+/// The queue lock may be held while a queued switch is applied, so wait for it off the
+/// async runtime.
+async fn drop_pending_switch() -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(supersede_pending_switch)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Switch to a different account
+#[tauri::command]
+pub async fn switch_account(account_id: String) -> Result<(), String> {
+    drop_pending_switch().await?;
+
+    // Write to ~/.codex/auth.json, then update the active account and last_used_at
+    activate_account(&account_id).map_err(|e| e.to_string())?;
     Ok(())
 }
 
-/// Remove an account
+/// List recent account switches, oldest first
+#[tauri::command]
+pub async fn get_switch_history() -> Result<Vec<SwitchJournalEntry>, String> {
+    let journal = load_switch_journal().map_err(|e| e.to_string())?;
+    Ok(journal.entries)
+}
+
+/// List recorded activations, oldest first, optionally for one account
+#[tauri::command]
+pub async fn get_activation_history(
+    account_id: Option<String>,
+) -> Result<Vec<ActivationRecord>, String> {
+    let log = load_activation_log().map_err(|e| e.to_string())?;
+    Ok(log
+        .entries
+        .into_iter()
+        .filter(|record| {
+            account_id
+                .as_ref()
+                .is_none_or(|id| record.account_id == *id)
+        })
+        .collect())
+}
+
+/// Active time per account and week, switch frequency, and accounts unused for `unused_days`
+#[tauri::command]
+pub async fn get_account_stats(
+    weeks: Option<u32>,
+    unused_days: Option<u32>,
+) -> Result<AccountUsageStats, String> {
+    // Out-of-range values are clamped when the statistics are computed
+    build_account_stats(
+        weeks.unwrap_or(DEFAULT_STATS_WEEKS),
+        unused_days.unwrap_or(DEFAULT_UNUSED_DAYS),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Undo the last switch, restoring the previous auth.json and active account
+#[tauri::command]
+pub async fn undo_last_switch() -> Result<SwitchUndoResult, String> {
+    drop_pending_switch().await?;
+    undo_last_stored_switch().map_err(|e| e.to_string())
+}
+
+/// Remove an account, optionally revoking its tokens at the issuer first.
+///
+/// The account goes to the trash, unless a token was revoked: a restore would then
+/// bring back credentials the issuer no longer accepts, so it is purged instead.
 #[tauri::command]
-pub async fn delete_account(account_id: String) -> Result<(), String> {
+pub async fn delete_account(
+    account_id: String,
+    revoke_tokens: Option<bool>,
+) -> Result<AccountDeletionReport, String> {
+    let revocation = if revoke_tokens.unwrap_or(false) {
+        let account = get_account(&account_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Account not found: {account_id}"))?;
+
+        let report = revoke_account_tokens(&account).await;
+        if let Err(err) = record_revocation(&report) {
+            eprintln!("[Accounts] Failed to record token revocation: {err:#}");
+        }
+        Some(report)
+    } else {
+        None
+    };
+
+    // The local record goes away even when revocation failed
     remove_account(&account_id).map_err(|e| e.to_string())?;
-    Ok(())
+    forget_usage(&account_id);
+    if revocation
+        .as_ref()
+        .is_some_and(|report| report.any_revoked())
+    {
+        purge_trash(Some(&account_id)).map_err(|e| e.to_string())?;
+    }
+
+    let warning = revocation
+        .as_ref()
+        .filter(|report| !report.all_revoked())
+        .map(|_| {
+            "The account was deleted, but its tokens could not be revoked and may stay valid until they expire. Sign out of all sessions at chatgpt.com to end them.".to_string()
+        });
+
+    Ok(AccountDeletionReport {
+        account_id,
+        revocation,
+        warning,
+    })
+}
+
+/// List recorded token revocation attempts, oldest first
+#[tauri::command]
+pub async fn get_token_revocations() -> Result<Vec<TokenRevocationReport>, String> {
+    let log = load_revocation_log().map_err(|e| e.to_string())?;
+    Ok(log.entries)
 }
 
 /// Rename an account
@@ -89,6 +257,121 @@ pub async fn rename_account(account_id: String, new_name: String) -> Result<(),
     Ok(())
 }
 
+/// Choose which workspace an account uses; `None` restores the token's own workspace.
+///
+/// For the active account auth.json is rewritten right away; this is not a switch, so
+/// nothing is journaled or snapshotted.
+#[tauri::command]
+pub async fn set_account_workspace(
+    account_id: String,
+    workspace_id: Option<String>,
+) -> Result<AccountInfo, String> {
+    let account =
+        set_stored_account_workspace(&account_id, workspace_id).map_err(|e| e.to_string())?;
+
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    let active_id = store.active_account_id.as_deref();
+    if active_id == Some(account.id.as_str()) {
+        write_account_auth_json(&account).map_err(|e| e.to_string())?;
+    }
+
+    Ok(AccountInfo::from_stored(&account, active_id))
+}
+
+/// Replace the tags of an account
+#[tauri::command]
+pub async fn set_account_tags(
+    account_id: String,
+    tags: Vec<String>,
+) -> Result<AccountInfo, String> {
+    let account = set_stored_account_tags(&account_id, &tags).map_err(|e| e.to_string())?;
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    Ok(AccountInfo::from_stored(
+        &account,
+        store.active_account_id.as_deref(),
+    ))
+}
+
+/// List the tags in use with how many accounts carry each
+#[tauri::command]
+pub async fn list_account_tags() -> Result<Vec<AccountTagSummary>, String> {
+    list_stored_tags().map_err(|e| e.to_string())
+}
+
+/// Rename a tag on every account; returns how many accounts changed
+#[tauri::command]
+pub async fn rename_account_tag(from: String, to: String) -> Result<usize, String> {
+    rename_tag(&from, &to).map_err(|e| e.to_string())
+}
+
+/// Fold several tags into one on every account; returns how many accounts changed
+#[tauri::command]
+pub async fn merge_account_tags(tags: Vec<String>, into: String) -> Result<usize, String> {
+    merge_tags(&tags, &into).map_err(|e| e.to_string())
+}
+
+/// Filter and sort accounts on the backend, pairing each with its latest fetched usage
+#[tauri::command]
+pub async fn query_accounts(query: Option<AccountQuery>) -> Result<Vec<AccountQueryMatch>, String> {
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    let usage = latest_usage();
+    let active_id = store.active_account_id.as_deref();
+
+    Ok(query_stored_accounts(
+        &store.accounts,
+        &usage,
+        active_id,
+        &query.unwrap_or_default(),
+    )
+    .into_iter()
+    .map(|(account, usage)| AccountQueryMatch {
+        account: AccountInfo::from_stored(account, active_id),
+        usage: usage.cloned(),
+    })
+    .collect())
+}
+
+/// Replace an account's free-form notes; `None` clears them
+#[tauri::command]
+pub async fn set_account_notes(
+    account_id: String,
+    notes: Option<String>,
+) -> Result<AccountInfo, String> {
+    let account = set_stored_account_notes(&account_id, notes).map_err(|e| e.to_string())?;
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    Ok(AccountInfo::from_stored(
+        &account,
+        store.active_account_id.as_deref(),
+    ))
+}
+
+/// Set a custom metadata field on an account, or remove it when `value` is `None`
+#[tauri::command]
+pub async fn set_account_metadata_field(
+    account_id: String,
+    key: String,
+    value: Option<String>,
+) -> Result<AccountInfo, String> {
+    let account = set_stored_metadata_field(&account_id, &key, value).map_err(|e| e.to_string())?;
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    Ok(AccountInfo::from_stored(
+        &account,
+        store.active_account_id.as_deref(),
+    ))
+}
+
+/// Find accounts whose name, email, plan, auth mode, tags, notes or metadata contain `text`
+#[tauri::command]
+pub async fn search_accounts(text: String) -> Result<Vec<AccountInfo>, String> {
+    let accounts = search_stored_accounts(&text).map_err(|e| e.to_string())?;
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    let active_id = store.active_account_id.as_deref();
+    Ok(accounts
+        .iter()
+        .map(|a| AccountInfo::from_stored(a, active_id))
+        .collect())
+}
+
 /// Persist account ordering
 #[tauri::command]
 pub async fn reorder_accounts(account_ids: Vec<String>) -> Result<(), String> {
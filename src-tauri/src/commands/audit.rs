@@ -0,0 +1,16 @@
+//! Audit log Tauri commands
+
+use crate::auth::{export_audit_log as export_stored_audit_log, query_audit_log};
+use crate::types::{AuditEntry, AuditLogQuery};
+
+/// List audit entries, oldest first, filtered by time range and account
+#[tauri::command]
+pub async fn get_audit_log(query: Option<AuditLogQuery>) -> Result<Vec<AuditEntry>, String> {
+    query_audit_log(&query.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Export matching audit entries to a JSON file; returns how many were written
+#[tauri::command]
+pub async fn export_audit_log(path: String, query: Option<AuditLogQuery>) -> Result<usize, String> {
+    export_stored_audit_log(&query.unwrap_or_default(), &path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,36 @@
+//! Encrypted account bundle Tauri commands
+
+use crate::auth::{export_accounts_bundle, import_accounts_bundle, preview_accounts_bundle};
+use crate::types::{BundleExportResult, BundleImportPlan, DuplicateStrategy};
+
+/// Export selected accounts, or all of them, to a passphrase-encrypted bundle
+#[tauri::command]
+pub async fn export_accounts(
+    path: String,
+    passphrase: String,
+    account_ids: Option<Vec<String>>,
+) -> Result<BundleExportResult, String> {
+    export_accounts_bundle(&path, &passphrase, account_ids.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Show what importing a bundle would add, update or skip
+#[tauri::command]
+pub async fn preview_accounts_import(
+    path: String,
+    passphrase: String,
+    on_duplicate: Option<DuplicateStrategy>,
+) -> Result<BundleImportPlan, String> {
+    preview_accounts_bundle(&path, &passphrase, on_duplicate.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Merge an encrypted bundle into the local store
+#[tauri::command]
+pub async fn import_accounts(
+    path: String,
+    passphrase: String,
+    on_duplicate: Option<DuplicateStrategy>,
+) -> Result<BundleImportPlan, String> {
+    import_accounts_bundle(&path, &passphrase, on_duplicate.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
@@ -1,13 +1,19 @@
 //! Tauri commands module
 
 pub mod account;
+pub mod audit;
+pub mod bundle;
 pub mod oauth;
 pub mod process;
 pub mod session;
+pub mod trash;
 pub mod usage;
 
 pub use account::*;
+pub use audit::*;
+pub use bundle::*;
 pub use oauth::*;
 pub use process::*;
 pub use session::*;
+pub use trash::*;
 pub use usage::*;
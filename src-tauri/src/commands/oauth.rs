@@ -1,153 +1,539 @@
 //! OAuth login Tauri commands
 
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use tauri::{AppHandle, Emitter};
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 
-use crate::auth::oauth_server::{start_oauth_login, wait_for_oauth_login, OAuthLoginResult};
+use crate::auth::oauth_server::{
+    complete_manual_oauth_login, start_manual_oauth_login, start_oauth_login, wait_for_oauth_login,
+    ManualOAuthFlow, OAuthLoginResult, OAuthProgressReporter, LOGIN_TIMEOUT,
+};
 use crate::auth::{
-    add_account, load_accounts, replace_account_chatgpt_credentials, set_active_account,
+    add_or_merge_account, load_accounts, replace_account_chatgpt_credentials, set_active_account,
     switch_to_account, touch_account,
 };
-use crate::types::{AccountInfo, AuthData, AuthMode, OAuthLoginInfo};
+use crate::types::{
+    AccountInfo, AuthData, AuthMode, OAuthLoginInfo, OAuthLoginPhase, OAuthLoginProgress,
+    OAuthLoginStatus, StoredAccount,
+};
+
+/// Event emitted with the login status whenever an OAuth login changes phase
+pub const OAUTH_LOGIN_STATUS_EVENT: &str = "oauth-login-status";
 
 enum PendingOAuthMode {
     CreateAccount,
     ReconnectAccount { account_id: String },
 }
 
+enum PendingOAuthFlow {
+    /// The browser redirects to a local callback server
+    Callback {
+        rx: oneshot::Receiver<anyhow::Result<OAuthLoginResult>>,
+        cancel: CancellationToken,
+    },
+    /// The user pastes the redirect URL or code back into the app
+    Manual(ManualOAuthFlow),
+}
+
 struct PendingOAuth {
-    rx: oneshot::Receiver<anyhow::Result<OAuthLoginResult>>,
-    cancelled: Arc<AtomicBool>,
+    info: OAuthLoginInfo,
+    flow: PendingOAuthFlow,
     mode: PendingOAuthMode,
 }
 
-// Global state for pending OAuth login
-static PENDING_OAUTH: Mutex<Option<PendingOAuth>> = Mutex::new(None);
+impl PendingOAuth {
+    fn is_manual(&self) -> bool {
+        matches!(self.flow, PendingOAuthFlow::Manual(_))
+    }
 
-/// Start the OAuth login flow
-#[tauri::command]
-pub async fn start_login(account_name: String) -> Result<OAuthLoginInfo, String> {
-    // Cancel any previous pending flow so it does not keep the callback port occupied.
-    if let Some(previous) = {
-        let mut pending = PENDING_OAUTH.lock().unwrap();
-        pending.take()
-    } {
-        previous.cancelled.store(true, Ordering::Relaxed);
+    /// Stop the callback server, if this flow has one
+    fn cancel(self) {
+        if let PendingOAuthFlow::Callback { cancel, .. } = self.flow {
+            cancel.cancel();
+        }
     }
+}
 
-    let (info, rx, cancelled) = start_oauth_login(account_name)
-        .await
-        .map_err(|e| e.to_string())?;
+// Pending OAuth logins in the order they were started
+static PENDING_OAUTH: Mutex<Vec<PendingOAuth>> = Mutex::new(Vec::new());
 
-    // Store the receiver for later
-    {
-        let mut pending = PENDING_OAUTH.lock().unwrap();
-        *pending = Some(PendingOAuth {
-            rx,
-            cancelled,
-            mode: PendingOAuthMode::CreateAccount,
-        });
+/// Finished logins stay queryable this long
+const FINISHED_STATUS_TTL: Duration = Duration::minutes(10);
+
+// Status of recent OAuth logins, pending or finished
+static LOGIN_STATUSES: Mutex<Vec<OAuthLoginStatus>> = Mutex::new(Vec::new());
+
+fn login_timeout() -> Duration {
+    Duration::from_std(LOGIN_TIMEOUT).expect("login timeout fits chrono")
+}
+
+/// Mark manual logins past their deadline as timed out and forget old finished logins.
+///
+/// Callback logins report their own timeout when the listener stops.
+fn refresh_login_statuses(statuses: &mut Vec<OAuthLoginStatus>, now: DateTime<Utc>) {
+    for status in statuses.iter_mut() {
+        if status.manual && !status.phase.is_finished() && status.expires_at <= now {
+            status.phase = OAuthLoginPhase::TimedOut;
+            status.reason = None;
+            status.updated_at = now;
+        }
     }
 
-    Ok(info)
+    statuses.retain(|status| {
+        !status.phase.is_finished() || now - status.updated_at < FINISHED_STATUS_TTL
+    });
 }
 
-/// Start OAuth reconnect flow for an existing account
-#[tauri::command]
-pub async fn start_reconnect(account_id: String) -> Result<OAuthLoginInfo, String> {
-    let account_name = {
-        let store = load_accounts().map_err(|e| e.to_string())?;
-        let account = store
-            .accounts
-            .iter()
-            .find(|a| a.id == account_id)
-            .ok_or_else(|| format!("Account not found: {account_id}"))?;
+/// A status snapshot with the time left before the login times out
+fn status_snapshot(status: &OAuthLoginStatus, now: DateTime<Utc>) -> OAuthLoginStatus {
+    let mut snapshot = status.clone();
+    snapshot.remaining_secs = (!status.phase.is_finished())
+        .then(|| (status.expires_at - now).num_seconds().max(0) as u64);
+    snapshot
+}
+
+fn track_login_status(pending: &PendingOAuth, account_name: String) -> OAuthLoginStatus {
+    let now = Utc::now();
+    let status = OAuthLoginStatus {
+        login_id: pending.info.login_id.clone(),
+        account_name,
+        reconnect_account_id: match &pending.mode {
+            PendingOAuthMode::CreateAccount => None,
+            PendingOAuthMode::ReconnectAccount { account_id } => Some(account_id.clone()),
+        },
+        manual: pending.is_manual(),
+        callback_port: pending.info.callback_port,
+        phase: OAuthLoginPhase::WaitingForBrowser,
+        reason: None,
+        browser_opened: false,
+        started_at: now,
+        updated_at: now,
+        expires_at: now + login_timeout(),
+        remaining_secs: None,
+    };
+
+    let mut statuses = LOGIN_STATUSES.lock().unwrap();
+    refresh_login_statuses(&mut statuses, now);
+    statuses.push(status.clone());
+    status_snapshot(&status, now)
+}
+
+/// Apply a phase change; `None` when the login is unknown or has already ended
+fn update_login_status(progress: OAuthLoginProgress) -> Option<OAuthLoginStatus> {
+    let now = Utc::now();
+    let mut statuses = LOGIN_STATUSES.lock().unwrap();
+    let status = statuses
+        .iter_mut()
+        .find(|status| status.login_id == progress.login_id)?;
+
+    if status.phase.is_finished() {
+        return None;
+    }
+
+    if progress.phase == OAuthLoginPhase::WaitingForBrowser && !status.manual {
+        status.browser_opened = true;
+    }
+    status.phase = progress.phase;
+    status.reason = progress.reason;
+    status.updated_at = now;
+
+    Some(status_snapshot(status, now))
+}
+
+fn emit_login_status(app: &AppHandle, status: &OAuthLoginStatus) {
+    if let Err(err) = app.emit(OAUTH_LOGIN_STATUS_EVENT, status) {
+        eprintln!("[OAuth] Failed to emit {OAUTH_LOGIN_STATUS_EVENT}: {err}");
+    }
+}
+
+fn record_login_phase(
+    app: &AppHandle,
+    login_id: &str,
+    phase: OAuthLoginPhase,
+    reason: Option<String>,
+) {
+    let progress = OAuthLoginProgress {
+        login_id: login_id.to_string(),
+        phase,
+        reason,
+    };
+    if let Some(status) = update_login_status(progress) {
+        emit_login_status(app, &status);
+    }
+}
+
+/// Add a login to the registry and start tracking its status.
+///
+/// Returns the flows it replaces: an older reconnect of the same account, and manual
+/// logins that timed out without a paste.
+fn register_pending_oauth(
+    info: &OAuthLoginInfo,
+    account_name: String,
+    flow: PendingOAuthFlow,
+    mode: PendingOAuthMode,
+) -> (OAuthLoginStatus, Vec<PendingOAuth>) {
+    let pending = PendingOAuth {
+        info: info.clone(),
+        flow,
+        mode,
+    };
+    let status = track_login_status(&pending, account_name);
+
+    let mut flows = PENDING_OAUTH.lock().unwrap();
+    let statuses = LOGIN_STATUSES.lock().unwrap();
+
+    let (replaced, kept): (Vec<_>, Vec<_>) = flows.drain(..).partition(|other| {
+        let expired_manual = other.is_manual()
+            && statuses
+                .iter()
+                .find(|status| status.login_id == other.info.login_id)
+                .is_none_or(|status| status.phase.is_finished());
+        let same_reconnect = matches!(
+            (&pending.mode, &other.mode),
+            (
+                PendingOAuthMode::ReconnectAccount { account_id },
+                PendingOAuthMode::ReconnectAccount { account_id: other_id },
+            ) if other_id == account_id
+        );
+        expired_manual || same_reconnect
+    });
+    *flows = kept;
+    flows.push(pending);
+
+    (status, replaced)
+}
 
-        if account.auth_mode != AuthMode::ChatGPT {
-            return Err("Reconnect is only available for ChatGPT OAuth accounts".to_string());
+/// Register a login, cancel the flows it replaces and announce its status
+fn start_pending_oauth(
+    app: &AppHandle,
+    info: &OAuthLoginInfo,
+    account_name: String,
+    flow: PendingOAuthFlow,
+    mode: PendingOAuthMode,
+) {
+    let (status, replaced) = register_pending_oauth(info, account_name, flow, mode);
+
+    for flow in replaced {
+        // Callback listeners report their own cancellation
+        if flow.is_manual() {
+            record_login_phase(app, &flow.info.login_id, OAuthLoginPhase::Cancelled, None);
         }
+        flow.cancel();
+    }
+
+    emit_login_status(app, &status);
+}
 
-        account.name.clone()
+/// Why a login is no longer pending, with the reason it ended when that is still known
+fn missing_login_error(login_id: &str) -> String {
+    let statuses = LOGIN_STATUSES.lock().unwrap();
+    match statuses
+        .iter()
+        .find(|status| status.login_id == login_id && status.phase.is_finished())
+    {
+        Some(status) => match &status.reason {
+            Some(reason) => format!("OAuth login {login_id} has already ended: {reason}"),
+            None => format!("OAuth login {login_id} has already ended"),
+        },
+        None => format!("No pending OAuth login: {login_id}"),
+    }
+}
+
+/// Remove a pending login matching `accept`; without `login_id`, the most recently started one
+fn take_pending_oauth(
+    login_id: Option<&str>,
+    accept: impl Fn(&PendingOAuth) -> bool,
+) -> Result<PendingOAuth, String> {
+    let mut pending = PENDING_OAUTH.lock().unwrap();
+
+    let index = match login_id {
+        Some(login_id) => pending
+            .iter()
+            .position(|flow| flow.info.login_id == login_id)
+            .ok_or_else(|| missing_login_error(login_id))?,
+        None => pending
+            .iter()
+            .rposition(&accept)
+            .ok_or_else(|| "No pending OAuth login".to_string())?,
     };
 
-    // Cancel any previous pending flow so it does not keep the callback port occupied.
-    if let Some(previous) = {
-        let mut pending = PENDING_OAUTH.lock().unwrap();
-        pending.take()
-    } {
-        previous.cancelled.store(true, Ordering::Relaxed);
+    if !accept(&pending[index]) {
+        return Err("Pending OAuth login does not match this request".to_string());
+    }
+
+    Ok(pending.remove(index))
+}
+
+fn is_callback_login(pending: &PendingOAuth) -> bool {
+    matches!(pending.flow, PendingOAuthFlow::Callback { .. })
+        && matches!(pending.mode, PendingOAuthMode::CreateAccount)
+}
+
+fn is_callback_reconnect(pending: &PendingOAuth) -> bool {
+    matches!(pending.flow, PendingOAuthFlow::Callback { .. })
+        && matches!(pending.mode, PendingOAuthMode::ReconnectAccount { .. })
+}
+
+/// Forget a callback login whose listener ended without an account to store.
+///
+/// A succeeded login stays until `complete_login` or `complete_reconnect` stores its account.
+fn drop_ended_callback(login_id: &str) {
+    PENDING_OAUTH
+        .lock()
+        .unwrap()
+        .retain(|flow| flow.is_manual() || flow.info.login_id != login_id);
+}
+
+fn progress_emitter(app: AppHandle) -> OAuthProgressReporter {
+    Arc::new(move |progress: OAuthLoginProgress| {
+        let ended_without_account =
+            progress.phase.is_finished() && progress.phase != OAuthLoginPhase::Succeeded;
+        let login_id = progress.login_id.clone();
+
+        if let Some(status) = update_login_status(progress) {
+            emit_login_status(&app, &status);
+        }
+        if ended_without_account {
+            drop_ended_callback(&login_id);
+        }
+    })
+}
+
+/// Name of an account that can be reconnected through OAuth
+fn reconnect_account_name(account_id: &str) -> Result<String, String> {
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    let account = store
+        .accounts
+        .iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("Account not found: {account_id}"))?;
+
+    if account.auth_mode != AuthMode::ChatGPT {
+        return Err("Reconnect is only available for ChatGPT OAuth accounts".to_string());
     }
 
-    let (info, rx, cancelled) = start_oauth_login(account_name)
+    Ok(account.name.clone())
+}
+
+/// Start the OAuth login flow.
+///
+/// Logins already in progress keep running; each is addressed by its `login_id`.
+#[tauri::command]
+pub async fn start_login(app: AppHandle, account_name: String) -> Result<OAuthLoginInfo, String> {
+    let login = start_oauth_login(account_name.clone(), progress_emitter(app.clone()))
         .await
         .map_err(|e| e.to_string())?;
 
-    {
-        let mut pending = PENDING_OAUTH.lock().unwrap();
-        *pending = Some(PendingOAuth {
-            rx,
-            cancelled,
-            mode: PendingOAuthMode::ReconnectAccount { account_id },
-        });
-    }
+    start_pending_oauth(
+        &app,
+        &login.info,
+        account_name,
+        PendingOAuthFlow::Callback {
+            rx: login.rx,
+            cancel: login.cancel,
+        },
+        PendingOAuthMode::CreateAccount,
+    );
+
+    Ok(login.info)
+}
+
+/// Start OAuth reconnect flow for an existing account
+#[tauri::command]
+pub async fn start_reconnect(app: AppHandle, account_id: String) -> Result<OAuthLoginInfo, String> {
+    let account_name = reconnect_account_name(&account_id)?;
+
+    let login = start_oauth_login(account_name.clone(), progress_emitter(app.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    start_pending_oauth(
+        &app,
+        &login.info,
+        account_name,
+        PendingOAuthFlow::Callback {
+            rx: login.rx,
+            cancel: login.cancel,
+        },
+        PendingOAuthMode::ReconnectAccount { account_id },
+    );
+
+    Ok(login.info)
+}
+
+/// Start a login that is finished by pasting the redirect URL, for remote or headless machines.
+///
+/// With `reconnect_account_id`, the pasted login replaces that account's credentials.
+#[tauri::command]
+pub async fn start_manual_login(
+    app: AppHandle,
+    account_name: String,
+    reconnect_account_id: Option<String>,
+) -> Result<OAuthLoginInfo, String> {
+    let (account_name, mode) = match reconnect_account_id {
+        Some(account_id) => (
+            reconnect_account_name(&account_id)?,
+            PendingOAuthMode::ReconnectAccount { account_id },
+        ),
+        None => (account_name, PendingOAuthMode::CreateAccount),
+    };
+
+    let (info, flow) = start_manual_oauth_login(account_name.clone());
+    start_pending_oauth(
+        &app,
+        &info,
+        account_name,
+        PendingOAuthFlow::Manual(flow),
+        mode,
+    );
 
     Ok(info)
 }
 
-/// Wait for the OAuth login to complete and add the account
+/// Finish a manual login with the pasted redirect URL; its `state` must match the login
 #[tauri::command]
-pub async fn complete_login() -> Result<AccountInfo, String> {
-    let pending = {
-        let mut pending = PENDING_OAUTH.lock().unwrap();
-        pending
-            .take()
-            .ok_or_else(|| "No pending OAuth login".to_string())?
+pub async fn complete_manual_login(
+    app: AppHandle,
+    pasted: String,
+    login_id: Option<String>,
+) -> Result<AccountInfo, String> {
+    let pending = take_pending_oauth(login_id.as_deref(), PendingOAuth::is_manual)?;
+    let login_id = pending.info.login_id.clone();
+
+    let PendingOAuth {
+        flow: PendingOAuthFlow::Manual(flow),
+        mode,
+        ..
+    } = pending
+    else {
+        return Err("Pending OAuth flow is not a manual login".to_string());
     };
 
-    if !matches!(pending.mode, PendingOAuthMode::CreateAccount) {
-        return Err("Pending OAuth flow is not a new account login".to_string());
+    let timed_out = {
+        let mut statuses = LOGIN_STATUSES.lock().unwrap();
+        refresh_login_statuses(&mut statuses, Utc::now());
+        statuses
+            .iter()
+            .find(|status| status.login_id == login_id)
+            .is_none_or(|status| status.phase == OAuthLoginPhase::TimedOut)
+    };
+    if timed_out {
+        return Err("OAuth login timed out".to_string());
     }
 
-    let account = wait_for_oauth_login(pending.rx)
-        .await
-        .map_err(|e| e.to_string())?;
+    record_login_phase(&app, &login_id, OAuthLoginPhase::CallbackReceived, None);
+    let code = match flow.authorization_code(&pasted) {
+        Ok(code) => code,
+        // Keep the flow so a mistyped paste can be retried
+        Err(e) => {
+            PENDING_OAUTH.lock().unwrap().push(PendingOAuth {
+                flow: PendingOAuthFlow::Manual(flow),
+                mode,
+                ..pending
+            });
+            record_login_phase(
+                &app,
+                &login_id,
+                OAuthLoginPhase::WaitingForBrowser,
+                Some(e.to_string()),
+            );
+            return Err(e.to_string());
+        }
+    };
+    record_login_phase(&app, &login_id, OAuthLoginPhase::StateVerified, None);
+
+    record_login_phase(&app, &login_id, OAuthLoginPhase::ExchangingTokens, None);
+    let result = match complete_manual_oauth_login(flow, &code).await {
+        Ok(account) => match mode {
+            PendingOAuthMode::CreateAccount => finish_login(account),
+            PendingOAuthMode::ReconnectAccount { account_id } => {
+                finish_reconnect(&account_id, account)
+            }
+        },
+        Err(e) => Err(format!("{e:#}")),
+    };
 
-    // Add the account to storage
-    let stored = add_account(account).map_err(|e| e.to_string())?;
+    match &result {
+        Ok(_) => record_login_phase(&app, &login_id, OAuthLoginPhase::Succeeded, None),
+        Err(e) => record_login_phase(&app, &login_id, OAuthLoginPhase::Failed, Some(e.clone())),
+    }
+    result
+}
 
-    // Make it active and switch to it
-    set_active_account(&stored.id).map_err(|e| e.to_string())?;
-    switch_to_account(&stored).map_err(|e| e.to_string())?;
-    touch_account(&stored.id).map_err(|e| e.to_string())?;
+/// Wait for the result of a pending callback flow
+async fn wait_for_callback(flow: PendingOAuthFlow) -> Result<StoredAccount, String> {
+    let PendingOAuthFlow::Callback { rx, .. } = flow else {
+        return Err("Pending OAuth flow waits for a pasted redirect URL".to_string());
+    };
 
-    let store = load_accounts().map_err(|e| e.to_string())?;
-    let active_id = store.active_account_id.as_deref();
+    wait_for_oauth_login(rx).await.map_err(|e| e.to_string())
+}
 
-    Ok(AccountInfo::from_stored(&stored, active_id))
+/// Record a failure to store a login whose callback already succeeded
+fn record_finish_error<T>(
+    app: &AppHandle,
+    login_id: &str,
+    result: Result<T, String>,
+) -> Result<T, String> {
+    if let Err(e) = &result {
+        record_login_phase(app, login_id, OAuthLoginPhase::Failed, Some(e.clone()));
+    }
+    result
+}
+
+/// Wait for the OAuth login to complete and add the account.
+///
+/// Without `login_id`, waits for the most recently started new-account login.
+#[tauri::command]
+pub async fn complete_login(
+    app: AppHandle,
+    login_id: Option<String>,
+) -> Result<AccountInfo, String> {
+    let pending = take_pending_oauth(login_id.as_deref(), is_callback_login)?;
+    let login_id = pending.info.login_id.clone();
+
+    let account = wait_for_callback(pending.flow).await?;
+    record_finish_error(&app, &login_id, finish_login(account))
 }
 
 /// Complete OAuth reconnect flow and replace stored credentials for existing account
 #[tauri::command]
-pub async fn complete_reconnect() -> Result<AccountInfo, String> {
-    let pending = {
-        let mut pending = PENDING_OAUTH.lock().unwrap();
-        pending
-            .take()
-            .ok_or_else(|| "No pending OAuth reconnect".to_string())?
+pub async fn complete_reconnect(
+    app: AppHandle,
+    login_id: Option<String>,
+) -> Result<AccountInfo, String> {
+    let pending = take_pending_oauth(login_id.as_deref(), is_callback_reconnect)?;
+    let login_id = pending.info.login_id.clone();
+
+    let PendingOAuthMode::ReconnectAccount { account_id } = pending.mode else {
+        return Err("Pending OAuth flow is not a reconnect".to_string());
     };
 
-    let account_id = match pending.mode {
-        PendingOAuthMode::ReconnectAccount { account_id } => account_id,
-        PendingOAuthMode::CreateAccount => {
-            return Err("Pending OAuth flow is not a reconnect".to_string())
-        }
-    };
+    let account = wait_for_callback(pending.flow).await?;
+    record_finish_error(&app, &login_id, finish_reconnect(&account_id, account))
+}
 
-    let account = wait_for_oauth_login(pending.rx)
-        .await
-        .map_err(|e| e.to_string())?;
+/// Store a freshly logged-in account, then switch to it
+fn finish_login(account: StoredAccount) -> Result<AccountInfo, String> {
+    // Add the account to storage, or refresh the record that already holds this login
+    let (stored, _) = add_or_merge_account(account).map_err(|e| e.to_string())?;
 
+    // Switch to it and make it active
+    switch_to_account(&stored).map_err(|e| e.to_string())?;
+    set_active_account(&stored.id).map_err(|e| e.to_string())?;
+    touch_account(&stored.id).map_err(|e| e.to_string())?;
+
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    let active_id = store.active_account_id.as_deref();
+
+    Ok(AccountInfo::from_stored(&stored, active_id))
+}
+
+/// Replace an existing account's credentials with a fresh login, then switch to it
+fn finish_reconnect(account_id: &str, account: StoredAccount) -> Result<AccountInfo, String> {
     let email = account.email.clone();
     let plan_type = account.plan_type.clone();
 
@@ -164,7 +550,7 @@ pub async fn complete_reconnect() -> Result<AccountInfo, String> {
     };
 
     let updated = replace_account_chatgpt_credentials(
-        &account_id,
+        account_id,
         id_token,
         access_token,
         refresh_token,
@@ -174,8 +560,8 @@ pub async fn complete_reconnect() -> Result<AccountInfo, String> {
     )
     .map_err(|e| e.to_string())?;
 
-    set_active_account(&updated.id).map_err(|e| e.to_string())?;
     switch_to_account(&updated).map_err(|e| e.to_string())?;
+    set_active_account(&updated.id).map_err(|e| e.to_string())?;
     touch_account(&updated.id).map_err(|e| e.to_string())?;
 
     let store = load_accounts().map_err(|e| e.to_string())?;
@@ -184,12 +570,157 @@ pub async fn complete_reconnect() -> Result<AccountInfo, String> {
     Ok(AccountInfo::from_stored(&updated, active_id))
 }
 
-/// Cancel a pending OAuth login
+/// Cancel a pending OAuth login; without `login_id`, cancels every pending login
 #[tauri::command]
-pub async fn cancel_login() -> Result<(), String> {
-    let mut pending = PENDING_OAUTH.lock().unwrap();
-    if let Some(pending_oauth) = pending.take() {
-        pending_oauth.cancelled.store(true, Ordering::Relaxed);
+pub async fn cancel_login(app: AppHandle, login_id: Option<String>) -> Result<(), String> {
+    let cancelled: Vec<PendingOAuth> = {
+        let mut pending = PENDING_OAUTH.lock().unwrap();
+        match login_id {
+            Some(login_id) => pending
+                .iter()
+                .position(|flow| flow.info.login_id == login_id)
+                .map(|index| pending.remove(index))
+                .into_iter()
+                .collect(),
+            None => pending.drain(..).collect(),
+        }
+    };
+
+    for flow in cancelled {
+        // Callback listeners report their own cancellation
+        if flow.is_manual() {
+            record_login_phase(&app, &flow.info.login_id, OAuthLoginPhase::Cancelled, None);
+        }
+        flow.cancel();
     }
     Ok(())
 }
+
+/// Status of a pending or recently finished OAuth login
+#[tauri::command]
+pub async fn get_login_status(login_id: String) -> Result<OAuthLoginStatus, String> {
+    let now = Utc::now();
+    let mut statuses = LOGIN_STATUSES.lock().unwrap();
+    refresh_login_statuses(&mut statuses, now);
+
+    statuses
+        .iter()
+        .find(|status| status.login_id == login_id)
+        .map(|status| status_snapshot(status, now))
+        .ok_or_else(|| format!("Unknown OAuth login: {login_id}"))
+}
+
+/// OAuth logins that have not ended yet, oldest first
+#[tauri::command]
+pub async fn list_pending_logins() -> Result<Vec<OAuthLoginStatus>, String> {
+    let now = Utc::now();
+    let mut statuses = LOGIN_STATUSES.lock().unwrap();
+    refresh_login_statuses(&mut statuses, now);
+
+    Ok(statuses
+        .iter()
+        .filter(|status| !status.phase.is_finished())
+        .map(|status| status_snapshot(status, now))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        refresh_login_statuses, register_pending_oauth, take_pending_oauth, track_login_status,
+        update_login_status, PendingOAuth, PendingOAuthFlow, PendingOAuthMode, PENDING_OAUTH,
+    };
+    use crate::auth::oauth_server::start_manual_oauth_login;
+    use crate::types::{OAuthLoginPhase, OAuthLoginProgress};
+    use chrono::{Duration, Utc};
+
+    fn start_manual(account_name: &str, mode: PendingOAuthMode) -> String {
+        let (info, flow) = start_manual_oauth_login(account_name.to_string());
+        register_pending_oauth(
+            &info,
+            account_name.to_string(),
+            PendingOAuthFlow::Manual(flow),
+            mode,
+        );
+        info.login_id
+    }
+
+    #[test]
+    fn keeps_concurrent_logins_apart() {
+        let reconnect = |account_id: &str| PendingOAuthMode::ReconnectAccount {
+            account_id: account_id.to_string(),
+        };
+        let first = start_manual("First", PendingOAuthMode::CreateAccount);
+        let second = start_manual("Second", PendingOAuthMode::CreateAccount);
+        let stale_reconnect = start_manual("Work", reconnect("acc-1"));
+        let reconnect_id = start_manual("Work", reconnect("acc-1"));
+
+        // A second reconnect of the same account replaces the first
+        assert!(take_pending_oauth(Some(&stale_reconnect), |_| true).is_err());
+
+        let is_new_account =
+            |pending: &super::PendingOAuth| matches!(pending.mode, PendingOAuthMode::CreateAccount);
+        let latest = take_pending_oauth(None, is_new_account).expect("latest login");
+        assert_eq!(latest.info.login_id, second);
+
+        let by_id = take_pending_oauth(Some(&first), is_new_account).expect("first login");
+        assert_eq!(by_id.info.login_id, first);
+        assert!(take_pending_oauth(Some(&reconnect_id), is_new_account).is_err());
+
+        PENDING_OAUTH.lock().unwrap().clear();
+    }
+
+    fn progress(login_id: &str, phase: OAuthLoginPhase) -> OAuthLoginProgress {
+        OAuthLoginProgress {
+            login_id: login_id.to_string(),
+            phase,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn tracks_phases_until_the_login_ends() {
+        let (info, flow) = start_manual_oauth_login("Work".to_string());
+        let pending = PendingOAuth {
+            info: info.clone(),
+            flow: PendingOAuthFlow::Manual(flow),
+            mode: PendingOAuthMode::CreateAccount,
+        };
+        let login_id = info.login_id.as_str();
+
+        let started = track_login_status(&pending, "Work".to_string());
+        assert_eq!(started.phase, OAuthLoginPhase::WaitingForBrowser);
+        assert!(started.remaining_secs.is_some_and(|secs| secs > 290));
+
+        let exchanging = update_login_status(progress(login_id, OAuthLoginPhase::ExchangingTokens))
+            .expect("pending login");
+        assert_eq!(exchanging.phase, OAuthLoginPhase::ExchangingTokens);
+
+        let done = update_login_status(progress(login_id, OAuthLoginPhase::Succeeded))
+            .expect("pending login");
+        assert_eq!(done.remaining_secs, None);
+        // A finished login does not change phase again
+        assert!(update_login_status(progress(login_id, OAuthLoginPhase::Cancelled)).is_none());
+    }
+
+    #[test]
+    fn times_out_manual_logins_after_the_deadline() {
+        let (info, flow) = start_manual_oauth_login("Work".to_string());
+        let pending = PendingOAuth {
+            info: info.clone(),
+            flow: PendingOAuthFlow::Manual(flow),
+            mode: PendingOAuthMode::CreateAccount,
+        };
+        let mut statuses = vec![track_login_status(&pending, "Work".to_string())];
+
+        refresh_login_statuses(&mut statuses, Utc::now() + Duration::minutes(4));
+        assert_eq!(statuses[0].phase, OAuthLoginPhase::WaitingForBrowser);
+
+        refresh_login_statuses(&mut statuses, Utc::now() + Duration::minutes(6));
+        assert_eq!(statuses[0].phase, OAuthLoginPhase::TimedOut);
+
+        // Finished logins are forgotten after a while
+        refresh_login_statuses(&mut statuses, Utc::now() + Duration::minutes(30));
+        assert!(statuses.is_empty());
+    }
+}
@@ -1,126 +1,162 @@
 //! Process detection commands
 
-use std::process::Command;
+use std::time::Duration;
 
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
+use tauri::{AppHandle, Emitter};
 
-#[cfg(windows)]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+use crate::process::{
+    cancel_pending_switch as cancel_stored_pending_switch, find_codex_processes_with_homes,
+    get_process_detection_settings, load_pending_switch, poll_pending_switch, queue_pending_switch,
+    set_process_detection_settings, stop_codex_and_switch, supersede_pending_switch,
+    DEFAULT_STOP_TIMEOUT,
+};
+use crate::types::{
+    CodexProcessDetails, ForcedSwitchReport, PendingSwitch, PendingSwitchOutcome,
+    ProcessDetectionSettings,
+};
+
+/// Event emitted when a queued switch has been applied
+pub const PENDING_SWITCH_APPLIED_EVENT: &str = "pending-switch-applied";
+/// Event emitted when a queued switch could not be applied
+pub const PENDING_SWITCH_FAILED_EVENT: &str = "pending-switch-failed";
+
+const PENDING_SWITCH_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 /// Information about running Codex processes
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct CodexProcessInfo {
-    /// Number of running codex processes
+    /// Number of running codex processes that share the Codex home a switch writes to
     pub count: usize,
-    /// Whether switching is allowed (no processes running)
+    /// Whether switching is allowed (no process shares the Codex home)
     pub can_switch: bool,
-    /// Process IDs of running codex processes
+    /// Process IDs of the processes that block a switch
     pub pids: Vec<u32>,
+    /// Every detected codex process, including ones using another Codex home
+    pub processes: Vec<CodexProcessDetails>,
 }
 
 /// Check for running Codex processes
 #[tauri::command]
 pub async fn check_codex_processes() -> Result<CodexProcessInfo, String> {
-    let pids = find_codex_processes().map_err(|e| e.to_string())?;
+    let processes = find_codex_processes_with_homes().map_err(|e| e.to_string())?;
+
+    let pids: Vec<u32> = processes
+        .iter()
+        .filter(|process| process.blocks_switch)
+        .map(|process| process.pid)
+        .collect();
     let count = pids.len();
 
     Ok(CodexProcessInfo {
         count,
         can_switch: count == 0,
         pids,
+        processes,
     })
 }
 
-/// Find all running codex processes
-fn find_codex_processes() -> anyhow::Result<Vec<u32>> {
-    let mut pids = Vec::new();
-
-    #[cfg(unix)]
-    {
-        // Use pgrep to find codex processes (exact match for "codex" command)
-        let output = Command::new("pgrep")
-            .args(["-x", "codex"]) // -x for exact match
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                for line in stdout.lines() {
-                    if let Ok(pid) = line.trim().parse::<u32>() {
-                        // Exclude our own process
-                        if pid != std::process::id() {
-                            pids.push(pid);
-                        }
-                    }
-                }
-            }
-        }
-
-        // Use ps with custom format to get the actual command name
-        // %c = command name only, %p = pid
-        let output = Command::new("ps").args(["-eo", "pid,comm"]).output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines().skip(1) {
-                // Skip header
-                let parts: Vec<&str> = line.trim().split_whitespace().collect();
-                if parts.len() >= 2 {
-                    let command = parts[1..].join(" ");
-
-                    // Only match if the actual command/binary name is "codex"
-                    // This excludes "brew upgrade codex" because the command is "brew"
-                    let is_codex = command == "codex"
-                        || command.ends_with("/codex")
-                        || command.starts_with("codex ");
-
-                    // Skip our own app
-                    let is_switcher = command.contains("codex-switcher")
-                        || command.contains("Codex Switcher")
-                        || command.contains("Codex Usage Inspector");
-
-                    if is_codex && !is_switcher {
-                        if let Ok(pid) = parts[0].parse::<u32>() {
-                            if pid != std::process::id() && !pids.contains(&pid) {
-                                pids.push(pid);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+#[tauri::command]
+pub async fn get_process_detection() -> Result<ProcessDetectionSettings, String> {
+    get_process_detection_settings().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_process_detection(detection: ProcessDetectionSettings) -> Result<(), String> {
+    set_process_detection_settings(detection).map_err(|e| e.to_string())
+}
+
+/// Restore the built-in detection patterns and return them
+#[tauri::command]
+pub async fn reset_process_detection() -> Result<ProcessDetectionSettings, String> {
+    let detection = ProcessDetectionSettings::default();
+    set_process_detection_settings(detection.clone()).map_err(|e| e.to_string())?;
+    Ok(detection)
+}
+
+fn emit_pending_switch_outcome(app: &AppHandle, outcome: &PendingSwitchOutcome) {
+    let event = if outcome.applied {
+        PENDING_SWITCH_APPLIED_EVENT
+    } else {
+        PENDING_SWITCH_FAILED_EVENT
+    };
+
+    if let Err(err) = app.emit(event, outcome) {
+        eprintln!("[Switch] Failed to emit {event}: {err}");
     }
+}
 
-    #[cfg(windows)]
-    {
-        // Use tasklist on Windows - match exact "codex.exe"
-        let output = Command::new("tasklist")
-            // Prevent a console window from flashing when this command is invoked from the GUI app.
-            .creation_flags(CREATE_NO_WINDOW)
-            .args(["/FI", "IMAGENAME eq codex.exe", "/FO", "CSV", "/NH"])
-            .output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                // CSV format: "name","pid",...
-                let parts: Vec<&str> = line.split(',').collect();
-                if parts.len() > 1 {
-                    let name = parts[0].trim_matches('"').to_lowercase();
-                    // Only match exact "codex.exe", not "codex-switcher.exe"
-                    if name == "codex.exe" {
-                        let pid_str = parts[1].trim_matches('"');
-                        if let Ok(pid) = pid_str.parse::<u32>() {
-                            if pid != std::process::id() {
-                                pids.push(pid);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+/// Check the queued switch once and emit its outcome if it was resolved
+async fn process_pending_switch(app: &AppHandle) {
+    // Scanning processes and waiting for the queue lock block, so keep them off the async runtime
+    match tauri::async_runtime::spawn_blocking(poll_pending_switch).await {
+        Ok(Ok(Some(outcome))) => emit_pending_switch_outcome(app, &outcome),
+        Ok(Ok(None)) => {}
+        Ok(Err(err)) => eprintln!("[Switch] Failed to check queued switch: {err:#}"),
+        Err(err) => eprintln!("[Switch] Queued switch check did not finish: {err}"),
     }
+}
 
-    Ok(pids)
+/// Background task that applies a queued switch once blocking Codex sessions exit.
+///
+/// Started from the app setup hook, so a switch queued before a restart still applies.
+pub async fn watch_pending_switch(app: AppHandle) {
+    let mut interval = tokio::time::interval(PENDING_SWITCH_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        process_pending_switch(&app).await;
+    }
+}
+
+/// Queue a switch that is applied as soon as no running Codex session shares the Codex home
+#[tauri::command]
+pub async fn queue_switch(app: AppHandle, account_id: String) -> Result<PendingSwitch, String> {
+    // Queueing takes the queue lock and scans processes, so keep it off the async runtime
+    let pending = tauri::async_runtime::spawn_blocking(move || queue_pending_switch(&account_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    // Apply right away when nothing is blocking
+    process_pending_switch(&app).await;
+
+    Ok(pending)
+}
+
+#[tauri::command]
+pub async fn get_pending_switch() -> Result<Option<PendingSwitch>, String> {
+    load_pending_switch().map_err(|e| e.to_string())
+}
+
+/// Cancel the queued switch, returning it if there was one
+#[tauri::command]
+pub async fn cancel_pending_switch() -> Result<Option<PendingSwitch>, String> {
+    tauri::async_runtime::spawn_blocking(cancel_stored_pending_switch)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Stop the Codex sessions sharing the Codex home, then switch accounts.
+///
+/// Sends SIGTERM and waits up to `timeout_secs` (default 10); SIGKILL is only sent
+/// when `allow_kill` is set. Processes owned by other users are never signalled.
+#[tauri::command]
+pub async fn force_switch_account(
+    account_id: String,
+    timeout_secs: Option<u64>,
+    allow_kill: Option<bool>,
+) -> Result<ForcedSwitchReport, String> {
+    let timeout = timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_STOP_TIMEOUT);
+    let allow_kill = allow_kill.unwrap_or(false);
+
+    // Waiting for processes to exit and for the queue lock blocks, so keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        supersede_pending_switch()?;
+        stop_codex_and_switch(&account_id, timeout, allow_kill)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
 }
@@ -1,7 +1,12 @@
 //! Session summary and snapshot Tauri commands
 
-use crate::auth::{build_current_auth_summary, create_auth_snapshot_file};
-use crate::types::CurrentAuthSummary;
+use crate::auth::{
+    build_current_auth_summary, create_auth_snapshot_file, get_snapshot_retention_policy,
+    list_snapshot_files, prune_snapshots, set_snapshot_retention_policy,
+};
+use crate::types::{
+    CurrentAuthSummary, SnapshotFileInfo, SnapshotPruneReport, SnapshotRetentionPolicy,
+};
 
 #[tauri::command]
 pub async fn get_current_auth_summary() -> Result<CurrentAuthSummary, String> {
@@ -13,9 +18,34 @@ pub async fn create_auth_snapshot() -> Result<String, String> {
     create_auth_snapshot_file().map_err(|e| e.to_string())
 }
 
+/// List saved session snapshots, newest first
+#[tauri::command]
+pub async fn list_auth_snapshots() -> Result<Vec<SnapshotFileInfo>, String> {
+    list_snapshot_files().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_snapshot_retention() -> Result<SnapshotRetentionPolicy, String> {
+    get_snapshot_retention_policy().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_snapshot_retention(policy: SnapshotRetentionPolicy) -> Result<(), String> {
+    set_snapshot_retention_policy(policy).map_err(|e| e.to_string())
+}
+
+/// Apply the snapshot retention policy, or preview it when `dry_run` is set
+#[tauri::command]
+pub async fn prune_auth_snapshots(dry_run: bool) -> Result<SnapshotPruneReport, String> {
+    prune_snapshots(dry_run).map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::auth::{build_snapshot_filename, derive_summary_from_auth, ensure_snapshots_dir};
+    use crate::auth::{
+        build_snapshot_filename, derive_summary_from_auth, ensure_snapshots_dir,
+        parse_snapshot_timestamp,
+    };
     use crate::types::{AuthDotJson, AuthMode, CurrentAuthStatus, TokenData};
     use chrono::Utc;
     use std::fs;
@@ -80,13 +110,25 @@ mod tests {
     #[test]
     fn snapshot_filename_changes_on_collision_index() {
         let now = Utc::now();
-        let first = build_snapshot_filename(now, 0);
-        let second = build_snapshot_filename(now, 1);
+        let first = build_snapshot_filename(now, None, 0);
+        let second = build_snapshot_filename(now, None, 1);
 
         assert_ne!(first, second);
         assert!(first.ends_with(".json"));
     }
 
+    #[test]
+    fn labeled_snapshot_filename_keeps_parseable_timestamp() {
+        let now = Utc::now();
+        let filename = build_snapshot_filename(now, Some("pre-switch"), 2);
+
+        assert!(filename.ends_with("-pre-switch-2.json"));
+        assert_eq!(
+            parse_snapshot_timestamp(&filename).map(|time| time.timestamp()),
+            Some(now.timestamp())
+        );
+    }
+
     #[test]
     fn creates_snapshots_directory_with_restrictive_permissions() {
         let test_home = temp_home_dir();
@@ -0,0 +1,45 @@
+//! Account trash Tauri commands
+
+use crate::auth::{
+    get_trash_settings as get_stored_trash_settings, list_trash as list_stored_trash,
+    load_accounts, purge_expired_trash, purge_trash as purge_stored_trash,
+    restore_account as restore_trashed_account, set_trash_settings as set_stored_trash_settings,
+};
+use crate::types::{AccountInfo, TrashSettings, TrashedAccountInfo};
+
+/// List deleted accounts still held in the trash
+#[tauri::command]
+pub async fn list_trash() -> Result<Vec<TrashedAccountInfo>, String> {
+    // Expired entries should never show up, even if the app has been open for days
+    purge_expired_trash().map_err(|e| e.to_string())?;
+    list_stored_trash().map_err(|e| e.to_string())
+}
+
+/// Move a deleted account back into the account list
+#[tauri::command]
+pub async fn restore_account(account_id: String) -> Result<AccountInfo, String> {
+    let account = restore_trashed_account(&account_id).map_err(|e| e.to_string())?;
+    let store = load_accounts().map_err(|e| e.to_string())?;
+    Ok(AccountInfo::from_stored(
+        &account,
+        store.active_account_id.as_deref(),
+    ))
+}
+
+/// Permanently delete one trashed account, or empty the trash when no id is given
+#[tauri::command]
+pub async fn purge_trash(account_id: Option<String>) -> Result<usize, String> {
+    purge_stored_trash(account_id.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_trash_settings() -> Result<TrashSettings, String> {
+    get_stored_trash_settings().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_trash_settings(settings: TrashSettings) -> Result<(), String> {
+    set_stored_trash_settings(settings).map_err(|e| e.to_string())?;
+    purge_expired_trash().map_err(|e| e.to_string())?;
+    Ok(())
+}
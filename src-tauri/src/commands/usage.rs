@@ -1,7 +1,7 @@
 //! Usage query Tauri commands
 
 use crate::api::usage::{get_account_usage, refresh_all_usage};
-use crate::auth::{get_account, load_accounts};
+use crate::auth::{account_has_tag, get_account, load_accounts};
 use crate::types::UsageInfo;
 
 /// Get usage info for a specific account
@@ -14,9 +14,14 @@ pub async fn get_usage(account_id: String) -> Result<UsageInfo, String> {
     get_account_usage(&account).await.map_err(|e| e.to_string())
 }
 
-/// Refresh usage info for all accounts
+/// Refresh usage info for all accounts, or only those carrying `tag`
 #[tauri::command]
-pub async fn refresh_all_accounts_usage() -> Result<Vec<UsageInfo>, String> {
+pub async fn refresh_all_accounts_usage(tag: Option<String>) -> Result<Vec<UsageInfo>, String> {
     let store = load_accounts().map_err(|e| e.to_string())?;
-    Ok(refresh_all_usage(&store.accounts).await)
+    let accounts: Vec<_> = store
+        .accounts
+        .into_iter()
+        .filter(|a| tag.as_deref().is_none_or(|tag| account_has_tag(a, tag)))
+        .collect();
+    Ok(refresh_all_usage(&accounts).await)
 }
@@ -3,13 +3,25 @@
 pub mod api;
 pub mod auth;
 pub mod commands;
+pub mod process;
 pub mod types;
 
 use commands::{
-    add_account_from_file, cancel_login, check_codex_processes, complete_login, complete_reconnect,
-    create_auth_snapshot, delete_account, get_active_account_info, get_current_auth_summary,
-    get_usage, list_accounts, refresh_all_accounts_usage, rename_account, reorder_accounts,
-    start_login, start_reconnect, switch_account,
+    add_account_from_file, cancel_login, cancel_pending_switch, check_codex_processes,
+    check_import_duplicate, complete_login, complete_manual_login, complete_reconnect,
+    create_auth_snapshot, delete_account, export_accounts, export_audit_log,
+    find_duplicate_accounts, force_switch_account, get_account_stats, get_activation_history,
+    get_active_account_info, get_audit_log, get_current_auth_summary, get_login_status,
+    get_pending_switch, get_process_detection, get_snapshot_retention, get_switch_history,
+    get_token_revocations, get_trash_settings, get_usage, import_accounts,
+    import_accounts_from_dir, list_account_tags, list_accounts, list_auth_snapshots,
+    list_pending_logins, list_trash, merge_account_tags, merge_duplicate_accounts,
+    preview_accounts_import, prune_auth_snapshots, purge_trash, query_accounts, queue_switch,
+    refresh_all_accounts_usage, rename_account, rename_account_tag, reorder_accounts,
+    reset_process_detection, restore_account, search_accounts, set_account_metadata_field,
+    set_account_notes, set_account_tags, set_account_workspace, set_process_detection,
+    set_snapshot_retention, set_trash_settings, start_login, start_manual_login, start_reconnect,
+    switch_account, undo_last_switch, watch_pending_switch,
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -18,19 +30,62 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_window_state::Builder::default().build())
+        .setup(|app| {
+            // Drop deleted accounts that have outlived the trash delay
+            if let Err(err) = auth::purge_expired_trash() {
+                eprintln!("[Trash] Failed to purge expired accounts: {err:#}");
+            }
+            // Apply a switch queued before the last restart once Codex exits
+            tauri::async_runtime::spawn(watch_pending_switch(app.handle().clone()));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Account management
             list_accounts,
             get_active_account_info,
             add_account_from_file,
+            import_accounts_from_dir,
+            check_import_duplicate,
+            find_duplicate_accounts,
+            merge_duplicate_accounts,
+            // Account bundles
+            export_accounts,
+            preview_accounts_import,
+            import_accounts,
             switch_account,
+            undo_last_switch,
+            get_switch_history,
+            get_activation_history,
+            get_account_stats,
             delete_account,
+            get_token_revocations,
+            list_trash,
+            restore_account,
+            purge_trash,
+            get_trash_settings,
+            set_trash_settings,
+            // Audit log
+            get_audit_log,
+            export_audit_log,
             rename_account,
+            set_account_workspace,
+            set_account_tags,
+            list_account_tags,
+            rename_account_tag,
+            merge_account_tags,
+            set_account_notes,
+            set_account_metadata_field,
+            search_accounts,
+            query_accounts,
             reorder_accounts,
             // OAuth
             start_login,
             complete_login,
+            start_manual_login,
+            complete_manual_login,
             cancel_login,
+            get_login_status,
+            list_pending_logins,
             start_reconnect,
             complete_reconnect,
             // Usage
@@ -38,9 +93,20 @@ pub fn run() {
             refresh_all_accounts_usage,
             // Process detection
             check_codex_processes,
+            get_process_detection,
+            set_process_detection,
+            reset_process_detection,
+            queue_switch,
+            get_pending_switch,
+            cancel_pending_switch,
+            force_switch_account,
             // Session snapshot manager
             get_current_auth_summary,
             create_auth_snapshot,
+            list_auth_snapshots,
+            get_snapshot_retention,
+            set_snapshot_retention,
+            prune_auth_snapshots,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,516 @@
+//! Codex process detection - finds running Codex CLI processes
+
+use std::process::Command;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use anyhow::Result;
+
+use crate::auth::storage::{load_settings, save_settings};
+use crate::types::{
+    CodexProcessDetails, ProcessDetectionSettings, ProcessMatchPattern, ProcessMatchTarget,
+    ProcessPatternMatch,
+};
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Whether a process name or path belongs to this app rather than the Codex CLI
+fn is_switcher_name(command: &str) -> bool {
+    command.contains("codex-switcher")
+        || command.contains("Codex Switcher")
+        || command.contains("Codex Usage Inspector")
+}
+
+fn is_switcher_process(process: &CodexProcessDetails) -> bool {
+    process.name.as_deref().is_some_and(is_switcher_name)
+        || process.exe.as_deref().is_some_and(is_switcher_name)
+        || process
+            .cmdline
+            .first()
+            .is_some_and(|arg| is_switcher_name(arg))
+}
+
+/// Match `value` against a glob where `*` stands for any run of characters.
+///
+/// Backslashes are treated as `/` on both sides so one pattern covers Windows paths.
+pub(crate) fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.replace('\\', "/");
+    let value = value.replace('\\', "/");
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcard: the prefix must be the whole value
+        return rest.is_empty();
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// File name of a path without a trailing `.exe`
+fn executable_name(path: &str) -> String {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let len = name.len();
+    if len > 4 && name.is_char_boundary(len - 4) && name[len - 4..].eq_ignore_ascii_case(".exe") {
+        name[..len - 4].to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn match_candidates(process: &CodexProcessDetails, target: ProcessMatchTarget) -> Vec<String> {
+    match target {
+        ProcessMatchTarget::Name => {
+            let mut names: Vec<String> = Vec::new();
+            let sources = process
+                .name
+                .iter()
+                .chain(process.exe.iter())
+                .chain(process.cmdline.first());
+            for name in sources.map(|source| executable_name(source)) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            names
+        }
+        ProcessMatchTarget::Exe => process.exe.iter().cloned().collect(),
+        ProcessMatchTarget::Argument => process.cmdline.clone(),
+    }
+}
+
+/// Find the first pattern that identifies a process as Codex
+pub(crate) fn match_process(
+    process: &CodexProcessDetails,
+    patterns: &[ProcessMatchPattern],
+) -> Option<ProcessPatternMatch> {
+    patterns.iter().find_map(|pattern| {
+        match_candidates(process, pattern.target)
+            .into_iter()
+            .find(|value| glob_match(&pattern.pattern, value))
+            .map(|matched_value| ProcessPatternMatch {
+                pattern: pattern.clone(),
+                matched_value,
+            })
+    })
+}
+
+/// Keep the processes matched by a pattern, excluding this app itself
+pub(crate) fn select_codex_processes(
+    processes: Vec<CodexProcessDetails>,
+    patterns: &[ProcessMatchPattern],
+) -> Vec<CodexProcessDetails> {
+    processes
+        .into_iter()
+        .filter(|process| process.pid != std::process::id() && !is_switcher_process(process))
+        .filter_map(|mut process| {
+            process.matched = Some(match_process(&process, patterns)?);
+            Some(process)
+        })
+        .collect()
+}
+
+/// Get the configured process detection patterns
+pub fn get_process_detection_settings() -> Result<ProcessDetectionSettings> {
+    Ok(load_settings()?.process_detection)
+}
+
+/// Persist new process detection patterns
+pub fn set_process_detection_settings(detection: ProcessDetectionSettings) -> Result<()> {
+    if detection.patterns.is_empty() {
+        anyhow::bail!("At least one process detection pattern is required");
+    }
+    if detection
+        .patterns
+        .iter()
+        .any(|pattern| pattern.pattern.trim().is_empty())
+    {
+        anyhow::bail!("Process detection patterns cannot be empty");
+    }
+
+    let mut settings = load_settings()?;
+    settings.process_detection = detection;
+    save_settings(&settings)
+}
+
+/// Find all running codex processes.
+///
+/// On Linux the details are read straight from `/proc`. Elsewhere, or when `/proc`
+/// is unavailable, `ps`/`tasklist` output is used instead. Either way a process is
+/// reported when one of the configured detection patterns matches it.
+pub fn find_codex_processes() -> Result<Vec<CodexProcessDetails>> {
+    let patterns = get_process_detection_settings()?.patterns;
+
+    #[cfg(target_os = "linux")]
+    {
+        match procfs::list_processes(
+            std::path::Path::new(procfs::PROC_ROOT),
+            procfs::clock_ticks_per_second(),
+        ) {
+            Ok(processes) => return Ok(select_codex_processes(processes, &patterns)),
+            Err(err) => {
+                eprintln!("[Process] Reading /proc failed, falling back to process tools: {err:#}")
+            }
+        }
+    }
+
+    Ok(select_codex_processes(
+        list_processes_with_tools(),
+        &patterns,
+    ))
+}
+
+/// Parse `ps -eo pid=,uid=,args=` output.
+///
+/// Arguments are split on whitespace, so arguments that contain spaces come back split.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub(crate) fn parse_ps_args_output(stdout: &str) -> Vec<CodexProcessDetails> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse::<u32>().ok()?;
+            let uid = parts.next()?.parse::<u32>().ok();
+            let cmdline: Vec<String> = parts.map(String::from).collect();
+            let mut process = CodexProcessDetails::from_pid(pid);
+            process.name = cmdline.first().map(|arg0| executable_name(arg0));
+            process.cmdline = cmdline;
+            process.uid = uid;
+            Some(process)
+        })
+        .collect()
+}
+
+/// List processes with the platform's process listing tools
+fn list_processes_with_tools() -> Vec<CodexProcessDetails> {
+    #[cfg(unix)]
+    {
+        // Full argument lists let wrapper patterns such as `node .../codex.js` match
+        let output = Command::new("ps").args(["-eo", "pid=,uid=,args="]).output();
+
+        if let Ok(output) = output {
+            if output.status.success() {
+                return parse_ps_args_output(&String::from_utf8_lossy(&output.stdout));
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        // tasklist only exposes image names, so only name patterns can match here
+        let output = Command::new("tasklist")
+            // Prevent a console window from flashing when this command is invoked from the GUI app.
+            .creation_flags(CREATE_NO_WINDOW)
+            .args(["/FO", "CSV", "/NH"])
+            .output();
+
+        if let Ok(output) = output {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let mut processes = Vec::new();
+            for line in stdout.lines() {
+                // CSV format: "name","pid",...
+                let parts: Vec<&str> = line.split(',').collect();
+                if parts.len() > 1 {
+                    let pid_str = parts[1].trim_matches('"');
+                    if let Ok(pid) = pid_str.parse::<u32>() {
+                        let mut process = CodexProcessDetails::from_pid(pid);
+                        process.name = Some(parts[0].trim_matches('"').to_string());
+                        processes.push(process);
+                    }
+                }
+            }
+            return processes;
+        }
+    }
+
+    Vec::new()
+}
+
+/// Direct `/proc` reader for Linux
+#[cfg(target_os = "linux")]
+pub(crate) mod procfs {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{Context, Result};
+    use chrono::{DateTime, Utc};
+
+    use crate::process::home::codex_home_from_environment;
+    use crate::types::{CodexHomeSource, CodexProcessDetails, ProcessInfoSource};
+
+    pub(crate) const PROC_ROOT: &str = "/proc";
+
+    /// Kernel clock ticks per second, used to convert process start times
+    pub(crate) fn clock_ticks_per_second() -> u64 {
+        // SAFETY: sysconf has no preconditions and only reads a system constant.
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        u64::try_from(ticks).ok().filter(|t| *t > 0).unwrap_or(100)
+    }
+
+    fn read_boot_time(proc_root: &Path) -> Option<i64> {
+        let stat = fs::read_to_string(proc_root.join("stat")).ok()?;
+        stat.lines()
+            .find_map(|line| line.strip_prefix("btime "))
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    /// Start time in clock ticks after boot (field 22 of `/proc/<pid>/stat`)
+    fn parse_start_ticks(stat: &str) -> Option<u64> {
+        // The command name may contain spaces and parentheses; fields resume after the last ')'
+        let rest = &stat[stat.rfind(')')? + 1..];
+        rest.split_whitespace().nth(19)?.parse().ok()
+    }
+
+    /// Real user ID from the `Uid:` line of `/proc/<pid>/status`
+    fn parse_status_uid(status: &str) -> Option<u32> {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("Uid:"))
+            .and_then(|ids| ids.split_whitespace().next())
+            .and_then(|uid| uid.parse().ok())
+    }
+
+    fn read_link_string(path: PathBuf) -> Option<String> {
+        fs::read_link(path).ok().map(|target| {
+            let target = target.display().to_string();
+            target
+                .strip_suffix(" (deleted)")
+                .map(String::from)
+                .unwrap_or(target)
+        })
+    }
+
+    /// Parse the NUL-separated `KEY=value` pairs of `/proc/<pid>/environ`
+    pub(crate) fn parse_environ(raw: &[u8]) -> HashMap<String, String> {
+        raw.split(|byte| *byte == 0)
+            .filter_map(|entry| {
+                let entry = String::from_utf8_lossy(entry);
+                let (key, value) = entry.split_once('=')?;
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn read_environ(dir: &Path) -> Option<HashMap<String, String>> {
+        fs::read(dir.join("environ"))
+            .ok()
+            .map(|raw| parse_environ(&raw))
+    }
+
+    /// Read everything `/proc` exposes about one process; `None` if it already exited
+    pub(crate) fn read_process(
+        proc_root: &Path,
+        pid: u32,
+        boot_time: Option<i64>,
+        ticks_per_second: u64,
+    ) -> Option<CodexProcessDetails> {
+        let dir = proc_root.join(pid.to_string());
+        let name = fs::read_to_string(dir.join("comm"))
+            .ok()?
+            .trim_end()
+            .to_string();
+
+        let cmdline = fs::read(dir.join("cmdline"))
+            .map(|raw| {
+                raw.split(|byte| *byte == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(|arg| String::from_utf8_lossy(arg).to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let started_at = fs::read_to_string(dir.join("stat"))
+            .ok()
+            .and_then(|stat| parse_start_ticks(&stat))
+            .zip(boot_time)
+            .and_then(|(ticks, boot)| {
+                let seconds = i64::try_from(ticks / ticks_per_second).ok()?;
+                DateTime::<Utc>::from_timestamp(boot + seconds, 0)
+            });
+
+        let uid = fs::read_to_string(dir.join("status"))
+            .ok()
+            .and_then(|status| parse_status_uid(&status));
+
+        let cwd = read_link_string(dir.join("cwd"));
+
+        // environ is only readable for processes we may ptrace, usually our own user's
+        let (codex_home, codex_home_source) = match read_environ(&dir) {
+            Some(vars) => codex_home_from_environment(&vars, cwd.as_deref()),
+            None => (None, CodexHomeSource::Unknown),
+        };
+
+        Some(CodexProcessDetails {
+            pid,
+            name: Some(name),
+            cmdline,
+            exe: read_link_string(dir.join("exe")),
+            cwd,
+            uid,
+            started_at,
+            source: ProcessInfoSource::Procfs,
+            matched: None,
+            codex_home,
+            codex_home_source,
+            account_id: None,
+            account_name: None,
+            blocks_switch: true,
+        })
+    }
+
+    /// Read every process listed in `/proc`
+    pub(crate) fn list_processes(
+        proc_root: &Path,
+        ticks_per_second: u64,
+    ) -> Result<Vec<CodexProcessDetails>> {
+        let entries = fs::read_dir(proc_root)
+            .with_context(|| format!("Failed to read {}", proc_root.display()))?;
+        let boot_time = read_boot_time(proc_root);
+
+        let mut processes: Vec<CodexProcessDetails> = entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| read_process(proc_root, pid, boot_time, ticks_per_second))
+            .collect();
+
+        processes.sort_by_key(|process| process.pid);
+        Ok(processes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, parse_ps_args_output, select_codex_processes};
+    use crate::types::{CodexHomeSource, ProcessDetectionSettings, ProcessMatchTarget};
+
+    #[test]
+    fn glob_matches_wildcards_and_windows_paths() {
+        assert!(glob_match("codex", "codex"));
+        assert!(!glob_match("codex", "codex-switcher"));
+        assert!(glob_match("@openai/codex@*", "@openai/codex@0.46.0"));
+        assert!(glob_match(
+            "*/@openai/codex/bin/codex.js",
+            r"C:\Users\me\AppData\Roaming\npm\node_modules\@openai\codex\bin\codex.js"
+        ));
+        assert!(glob_match("*/lib/*/codex", "/usr/lib/node/codex"));
+        assert!(!glob_match("*/lib/*/codex", "/usr/lib/codex-helper"));
+    }
+
+    #[test]
+    fn default_patterns_match_native_and_node_wrapped_codex() {
+        let listing = "\
+  101  501 /usr/local/bin/codex --model o3
+  102  501 node /home/me/.npm/lib/node_modules/@openai/codex/bin/codex.js
+  103  501 node /home/me/.npm/_npx/1a2b/node_modules/@openai/codex/bin/codex.js
+  104  501 brew upgrade codex
+  105  501 /opt/codex-switcher/codex-switcher
+  106  501 npm install -g @openai/codex@latest
+  107  501 npm view @openai/codex
+";
+        let patterns = ProcessDetectionSettings::default().patterns;
+
+        let processes = select_codex_processes(parse_ps_args_output(listing), &patterns);
+
+        let pids: Vec<u32> = processes.iter().map(|process| process.pid).collect();
+        assert_eq!(pids, vec![101, 102, 103]);
+        assert_eq!(processes[0].uid, Some(501));
+
+        let node = processes[1].matched.as_ref().expect("node match");
+        assert_eq!(node.pattern.target, ProcessMatchTarget::Argument);
+        assert_eq!(node.pattern.pattern, "*/@openai/codex/bin/codex.js");
+        assert_eq!(
+            node.matched_value,
+            "/home/me/.npm/lib/node_modules/@openai/codex/bin/codex.js"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_codex_process_details_from_proc() {
+        use super::procfs::list_processes;
+        use std::fs;
+        use std::path::Path;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        fn write_process(root: &Path, pid: u32, comm: &str, argv: &[&str], exe: &str) {
+            let dir = root.join(pid.to_string());
+            fs::create_dir_all(&dir).expect("create pid dir");
+            fs::write(dir.join("comm"), format!("{comm}\n")).expect("write comm");
+            fs::write(dir.join("cmdline"), format!("{}\0", argv.join("\0"))).expect("cmdline");
+            // Field 22 (start time) is 500 ticks after boot
+            fs::write(
+                dir.join("stat"),
+                format!("{pid} ({comm}) S 1 1 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 500 0 0"),
+            )
+            .expect("write stat");
+            fs::write(
+                dir.join("status"),
+                "Name:\tcodex\nUid:\t1000\t1000\t1000\t1000\n",
+            )
+            .expect("write status");
+            fs::write(
+                dir.join("environ"),
+                "HOME=/home/me\0CODEX_HOME=/srv/codex-work\0",
+            )
+            .expect("write environ");
+            std::os::unix::fs::symlink(exe, dir.join("exe")).expect("link exe");
+        }
+
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock drift")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("codex-switcher-proc-tests-{suffix}"));
+        fs::create_dir_all(&root).expect("create proc root");
+        fs::write(root.join("stat"), "cpu 0 0 0\nbtime 1700000000\n").expect("write stat");
+        write_process(
+            &root,
+            4242,
+            "codex",
+            &["codex", "--model", "o3"],
+            "/usr/local/bin/codex",
+        );
+        write_process(&root, 4343, "bash", &["bash"], "/usr/bin/bash");
+        write_process(
+            &root,
+            4444,
+            "codex-switcher",
+            &["codex-switcher"],
+            "/opt/codex-switcher/codex-switcher",
+        );
+
+        let processes = select_codex_processes(
+            list_processes(&root, 100).expect("scan proc"),
+            &ProcessDetectionSettings::default().patterns,
+        );
+
+        assert_eq!(processes.len(), 1);
+        let codex = &processes[0];
+        assert_eq!(codex.pid, 4242);
+        assert_eq!(codex.cmdline, vec!["codex", "--model", "o3"]);
+        assert_eq!(codex.exe.as_deref(), Some("/usr/local/bin/codex"));
+        assert_eq!(codex.uid, Some(1000));
+        assert_eq!(
+            codex.started_at.map(|time| time.timestamp()),
+            Some(1_700_000_005)
+        );
+        assert_eq!(codex.codex_home.as_deref(), Some("/srv/codex-work"));
+        assert_eq!(codex.codex_home_source, CodexHomeSource::Environment);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,165 @@
+//! Codex home resolution - works out which CODEX_HOME each running Codex process uses
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::auth::storage::load_accounts;
+use crate::auth::switcher::{find_account_matching_auth, get_codex_home};
+use crate::process::detection::find_codex_processes;
+use crate::types::{AuthDotJson, CodexHomeSource, CodexProcessDetails, StoredAccount};
+
+/// Resolve the Codex home from a process environment, the same way Codex does.
+///
+/// A relative `CODEX_HOME` is resolved against the process working directory.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn codex_home_from_environment(
+    vars: &HashMap<String, String>,
+    cwd: Option<&str>,
+) -> (Option<String>, CodexHomeSource) {
+    let non_empty = |name: &str| vars.get(name).filter(|value| !value.is_empty());
+
+    if let Some(codex_home) = non_empty("CODEX_HOME") {
+        let path = Path::new(codex_home);
+        let resolved = match cwd {
+            Some(cwd) if path.is_relative() => Path::new(cwd).join(path),
+            _ => path.to_path_buf(),
+        };
+        return (
+            Some(resolved.display().to_string()),
+            CodexHomeSource::Environment,
+        );
+    }
+
+    match non_empty("HOME") {
+        Some(home) => (
+            Some(Path::new(home).join(".codex").display().to_string()),
+            CodexHomeSource::Default,
+        ),
+        None => (None, CodexHomeSource::Unknown),
+    }
+}
+
+/// Whether two paths name the same directory, following symlinks when both exist
+pub(crate) fn same_path(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.components().eq(b.components()),
+    }
+}
+
+/// Find the stored account whose credentials are in `<codex_home>/auth.json`
+fn account_in_home<'a>(
+    codex_home: &Path,
+    accounts: &'a [StoredAccount],
+) -> Option<&'a StoredAccount> {
+    let content = fs::read_to_string(codex_home.join("auth.json")).ok()?;
+    let auth: AuthDotJson = serde_json::from_str(&content).ok()?;
+    find_account_matching_auth(accounts, &auth)
+}
+
+/// Fill in the account each process uses and whether it shares the home a switch writes to
+pub fn resolve_process_homes(processes: &mut [CodexProcessDetails]) -> Result<()> {
+    let switch_home = get_codex_home()?;
+    let accounts = load_accounts()?.accounts;
+    let mut accounts_by_home: HashMap<String, Option<(String, String)>> = HashMap::new();
+
+    for process in processes.iter_mut() {
+        let Some(codex_home) = process.codex_home.clone() else {
+            process.blocks_switch = true;
+            continue;
+        };
+
+        let home_path = PathBuf::from(&codex_home);
+        process.blocks_switch = same_path(&home_path, &switch_home);
+
+        let account = accounts_by_home
+            .entry(codex_home)
+            .or_insert_with(|| {
+                account_in_home(&home_path, &accounts)
+                    .map(|account| (account.id.clone(), account.name.clone()))
+            })
+            .clone();
+
+        if let Some((id, name)) = account {
+            process.account_id = Some(id);
+            process.account_name = Some(name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Find running codex processes with their Codex home and account resolved
+pub fn find_codex_processes_with_homes() -> Result<Vec<CodexProcessDetails>> {
+    let mut processes = find_codex_processes()?;
+    resolve_process_homes(&mut processes)?;
+    Ok(processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{codex_home_from_environment, same_path};
+    use crate::types::CodexHomeSource;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn environment(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_codex_home_like_codex_does() {
+        assert_eq!(
+            codex_home_from_environment(
+                &environment(&[("HOME", "/home/me"), ("CODEX_HOME", "/srv/codex-work")]),
+                None,
+            ),
+            (
+                Some("/srv/codex-work".to_string()),
+                CodexHomeSource::Environment
+            )
+        );
+        assert_eq!(
+            codex_home_from_environment(
+                &environment(&[("CODEX_HOME", "work-home")]),
+                Some("/home/me/project"),
+            ),
+            (
+                Some("/home/me/project/work-home".to_string()),
+                CodexHomeSource::Environment
+            )
+        );
+        assert_eq!(
+            codex_home_from_environment(
+                &environment(&[("HOME", "/home/me"), ("CODEX_HOME", "")]),
+                None
+            ),
+            (
+                Some("/home/me/.codex".to_string()),
+                CodexHomeSource::Default
+            )
+        );
+        assert_eq!(
+            codex_home_from_environment(&environment(&[]), None),
+            (None, CodexHomeSource::Unknown)
+        );
+    }
+
+    #[test]
+    fn compares_missing_paths_by_components() {
+        assert!(same_path(
+            Path::new("/nonexistent/codex-home/"),
+            Path::new("/nonexistent/codex-home")
+        ));
+        assert!(!same_path(
+            Path::new("/nonexistent/codex-home"),
+            Path::new("/nonexistent/other-home")
+        ));
+    }
+}
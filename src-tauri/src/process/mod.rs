@@ -0,0 +1,11 @@
+//! Codex process detection module
+
+pub mod detection;
+pub mod home;
+pub mod pending;
+pub mod terminate;
+
+pub use detection::*;
+pub use home::*;
+pub use pending::*;
+pub use terminate::*;
@@ -0,0 +1,173 @@
+//! Pending switch - a switch queued until the Codex sessions sharing the home exit
+
+use std::fs;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::auth::storage::{get_account, get_pending_switch_file, write_private_file};
+use crate::auth::switcher::{account_has_usable_credentials, activate_account};
+use crate::process::home::find_codex_processes_with_homes;
+use crate::types::{PendingSwitch, PendingSwitchOutcome};
+
+/// Serializes queueing, cancelling and applying so a cancelled switch is never applied
+static PENDING_SWITCH_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_pending_switch() -> std::sync::MutexGuard<'static, ()> {
+    PENDING_SWITCH_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Load the queued switch, if any
+pub fn load_pending_switch() -> Result<Option<PendingSwitch>> {
+    let path = get_pending_switch_file()?;
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read pending switch: {}", path.display()))?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse pending switch: {}", path.display()))
+}
+
+fn save_pending_switch(pending: &PendingSwitch) -> Result<()> {
+    let path = get_pending_switch_file()?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(pending).context("Failed to serialize pending switch")?;
+
+    write_private_file(&path, content.as_bytes())
+        .with_context(|| format!("Failed to write pending switch: {}", path.display()))
+}
+
+fn clear_pending_switch_file() -> Result<()> {
+    let path = get_pending_switch_file()?;
+
+    if path.exists() {
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove pending switch: {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// PIDs of running codex processes that share the Codex home a switch writes to
+fn blocking_pids() -> Result<Vec<u32>> {
+    Ok(find_codex_processes_with_homes()?
+        .into_iter()
+        .filter(|process| process.blocks_switch)
+        .map(|process| process.pid)
+        .collect())
+}
+
+/// Queue a switch to be applied once no Codex session shares the Codex home.
+///
+/// Replaces any switch that was already queued.
+pub fn queue_pending_switch(account_id: &str) -> Result<PendingSwitch> {
+    let _guard = lock_pending_switch();
+
+    let account =
+        get_account(account_id)?.with_context(|| format!("Account not found: {account_id}"))?;
+    if !account_has_usable_credentials(&account) {
+        anyhow::bail!(
+            "Missing stored credentials for account '{}'. Re-add this account to restore access.",
+            account.name
+        );
+    }
+
+    let pending = PendingSwitch {
+        id: Uuid::new_v4().to_string(),
+        account_id: account.id,
+        account_name: account.name,
+        queued_at: Utc::now(),
+        waiting_for_pids: blocking_pids()?,
+    };
+
+    save_pending_switch(&pending)?;
+    println!(
+        "[Switch] Queued switch to '{}' until {} Codex process(es) exit",
+        pending.account_name,
+        pending.waiting_for_pids.len()
+    );
+
+    Ok(pending)
+}
+
+/// Cancel the queued switch, returning it if there was one
+pub fn cancel_pending_switch() -> Result<Option<PendingSwitch>> {
+    let _guard = lock_pending_switch();
+
+    let pending = load_pending_switch()?;
+    clear_pending_switch_file()?;
+    Ok(pending)
+}
+
+/// Drop the queued switch because the user picked an account by hand.
+///
+/// Otherwise the watcher would later apply the older choice on top of the newer one.
+pub fn supersede_pending_switch() -> Result<()> {
+    if let Some(pending) = cancel_pending_switch()? {
+        println!(
+            "[Switch] Dropped queued switch to '{}' after a manual switch",
+            pending.account_name
+        );
+    }
+    Ok(())
+}
+
+/// Apply the queued switch if no Codex session shares the Codex home anymore.
+///
+/// Sessions started after the switch was queued keep it waiting too. Returns the
+/// outcome once the switch was applied or failed; the queue is cleared either way.
+pub fn poll_pending_switch() -> Result<Option<PendingSwitchOutcome>> {
+    let _guard = lock_pending_switch();
+
+    let Some(mut pending) = load_pending_switch()? else {
+        return Ok(None);
+    };
+
+    let pids = blocking_pids()?;
+    if !pids.is_empty() {
+        if pids != pending.waiting_for_pids {
+            pending.waiting_for_pids = pids;
+            save_pending_switch(&pending)?;
+        }
+        return Ok(None);
+    }
+
+    let error = activate_account(&pending.account_id)
+        .err()
+        .map(|err| format!("{err:#}"));
+    clear_pending_switch_file()?;
+
+    match &error {
+        None => println!(
+            "[Switch] Applied queued switch to '{}'",
+            pending.account_name
+        ),
+        Some(err) => eprintln!(
+            "[Switch] Queued switch to '{}' failed: {err}",
+            pending.account_name
+        ),
+    }
+
+    pending.waiting_for_pids.clear();
+    Ok(Some(PendingSwitchOutcome {
+        applied: error.is_none(),
+        error,
+        pending,
+        resolved_at: Utc::now(),
+    }))
+}
@@ -0,0 +1,346 @@
+//! Codex process termination - stops running Codex sessions before a forced switch
+
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+
+use crate::auth::storage::get_account;
+use crate::auth::switcher::{account_has_usable_credentials, activate_account};
+use crate::process::home::find_codex_processes_with_homes;
+use crate::process::pending::cancel_pending_switch;
+use crate::types::{
+    CodexProcessDetails, ForcedSwitchReport, ProcessStopOutcome, ProcessStopResult,
+};
+
+/// How long to wait for SIGTERM when the caller does not say
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Upper bound for a caller-provided SIGTERM timeout
+pub const MAX_STOP_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long to wait for a process to disappear after SIGKILL
+#[cfg_attr(not(unix), allow(dead_code))]
+const KILL_GRACE: Duration = Duration::from_secs(2);
+#[cfg_attr(not(unix), allow(dead_code))]
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn stop_result(
+    process: &CodexProcessDetails,
+    outcome: ProcessStopOutcome,
+    message: Option<String>,
+) -> ProcessStopResult {
+    ProcessStopResult {
+        pid: process.pid,
+        name: process.name.clone(),
+        outcome,
+        message,
+    }
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::io;
+
+    /// Send a signal; `Ok(false)` means the process no longer exists
+    pub(super) fn send(pid: u32, signal: libc::c_int) -> io::Result<bool> {
+        let pid = libc::pid_t::try_from(pid)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PID out of range"))?;
+
+        // SAFETY: kill has no memory-safety preconditions; pid is a positive process ID.
+        if unsafe { libc::kill(pid, signal) } == 0 {
+            return Ok(true);
+        }
+
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::ESRCH) => Ok(false),
+            _ => Err(err),
+        }
+    }
+
+    /// Whether a process still runs; zombies waiting to be reaped count as exited
+    pub(super) fn is_alive(pid: u32) -> bool {
+        let exists = match send(pid, 0) {
+            Ok(exists) => exists,
+            Err(err) => err.raw_os_error() == Some(libc::EPERM),
+        };
+
+        #[cfg(target_os = "linux")]
+        if exists {
+            let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).unwrap_or_default();
+            let state = stat
+                .rfind(')')
+                .and_then(|end| stat[end + 1..].split_whitespace().next());
+            return state != Some("Z");
+        }
+
+        exists
+    }
+
+    pub(super) fn current_uid() -> u32 {
+        // SAFETY: geteuid has no preconditions and cannot fail.
+        unsafe { libc::geteuid() }
+    }
+}
+
+#[cfg(unix)]
+fn wait_for_exit(pids: &[u32], timeout: Duration) -> Vec<u32> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let alive: Vec<u32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| signals::is_alive(*pid))
+            .collect();
+
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Send SIGTERM to each process, wait up to `timeout`, then SIGKILL survivors if `allow_kill`.
+///
+/// Processes owned by another user, or with an unknown owner, are never signalled.
+#[cfg(unix)]
+pub fn stop_processes(
+    processes: &[CodexProcessDetails],
+    timeout: Duration,
+    allow_kill: bool,
+) -> Vec<ProcessStopResult> {
+    let uid = signals::current_uid();
+    let mut results: Vec<Option<ProcessStopResult>> = vec![None; processes.len()];
+    let mut signalled: Vec<usize> = Vec::new();
+
+    for (index, process) in processes.iter().enumerate() {
+        let refusal = match process.uid {
+            Some(owner) if owner == uid => None,
+            Some(owner) => Some(format!("Owned by another user (uid {owner})")),
+            None => Some("Owner could not be determined".to_string()),
+        };
+        if let Some(message) = refusal {
+            results[index] = Some(stop_result(
+                process,
+                ProcessStopOutcome::Refused,
+                Some(message),
+            ));
+            continue;
+        }
+
+        match signals::send(process.pid, libc::SIGTERM) {
+            Ok(true) => signalled.push(index),
+            Ok(false) => {
+                results[index] = Some(stop_result(
+                    process,
+                    ProcessStopOutcome::AlreadyExited,
+                    None,
+                ))
+            }
+            Err(err) => {
+                results[index] = Some(stop_result(
+                    process,
+                    ProcessStopOutcome::Failed,
+                    Some(format!("SIGTERM failed: {err}")),
+                ))
+            }
+        }
+    }
+
+    let pids: Vec<u32> = signalled
+        .iter()
+        .map(|index| processes[*index].pid)
+        .collect();
+    let survivors = wait_for_exit(&pids, timeout);
+    let mut killed: Vec<usize> = Vec::new();
+
+    for index in signalled {
+        let process = &processes[index];
+        if !survivors.contains(&process.pid) {
+            results[index] = Some(stop_result(process, ProcessStopOutcome::Terminated, None));
+            continue;
+        }
+
+        if !allow_kill {
+            results[index] = Some(stop_result(
+                process,
+                ProcessStopOutcome::StillRunning,
+                Some(format!(
+                    "Did not exit within {}s of SIGTERM",
+                    timeout.as_secs()
+                )),
+            ));
+            continue;
+        }
+
+        match signals::send(process.pid, libc::SIGKILL) {
+            Ok(true) => killed.push(index),
+            // Exited between the last check and SIGKILL
+            Ok(false) => {
+                results[index] = Some(stop_result(process, ProcessStopOutcome::Terminated, None))
+            }
+            Err(err) => {
+                results[index] = Some(stop_result(
+                    process,
+                    ProcessStopOutcome::Failed,
+                    Some(format!("SIGKILL failed: {err}")),
+                ))
+            }
+        }
+    }
+
+    let pids: Vec<u32> = killed.iter().map(|index| processes[*index].pid).collect();
+    let survivors = wait_for_exit(&pids, KILL_GRACE);
+
+    for index in killed {
+        let process = &processes[index];
+        results[index] = Some(if survivors.contains(&process.pid) {
+            stop_result(
+                process,
+                ProcessStopOutcome::StillRunning,
+                Some("Still running after SIGKILL".to_string()),
+            )
+        } else {
+            stop_result(process, ProcessStopOutcome::Killed, None)
+        });
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+/// Signals are not available on this platform
+#[cfg(not(unix))]
+pub fn stop_processes(
+    processes: &[CodexProcessDetails],
+    _timeout: Duration,
+    _allow_kill: bool,
+) -> Vec<ProcessStopResult> {
+    processes
+        .iter()
+        .map(|process| {
+            stop_result(
+                process,
+                ProcessStopOutcome::Failed,
+                Some("Stopping processes is only supported on Unix".to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Stop the Codex sessions sharing the Codex home, then switch to `account_id`.
+///
+/// The account is checked before anything is stopped, and the switch only happens
+/// when every blocking process is gone afterwards. A queued switch is cancelled so
+/// it cannot override this one later.
+pub fn stop_codex_and_switch(
+    account_id: &str,
+    timeout: Duration,
+    allow_kill: bool,
+) -> Result<ForcedSwitchReport> {
+    // Never kill sessions for a switch that is bound to fail
+    let account =
+        get_account(account_id)?.with_context(|| format!("Account not found: {account_id}"))?;
+    if !account_has_usable_credentials(&account) {
+        anyhow::bail!(
+            "Missing stored credentials for account '{}'. Re-add this account to restore access.",
+            account.name
+        );
+    }
+
+    let timeout = timeout.min(MAX_STOP_TIMEOUT);
+    let blocking: Vec<CodexProcessDetails> = find_codex_processes_with_homes()?
+        .into_iter()
+        .filter(|process| process.blocks_switch)
+        .collect();
+
+    let processes = stop_processes(&blocking, timeout, allow_kill);
+    for result in &processes {
+        println!(
+            "[Process] Stopping PID {} before forced switch: {:?}",
+            result.pid, result.outcome
+        );
+    }
+
+    let remaining = find_codex_processes_with_homes()?
+        .into_iter()
+        .filter(|process| process.blocks_switch)
+        .count();
+
+    if remaining > 0 {
+        return Ok(ForcedSwitchReport {
+            account_id: account_id.to_string(),
+            switched: false,
+            processes,
+            error: Some(format!(
+                "{remaining} Codex process(es) still share the Codex home; auth.json was not changed"
+            )),
+        });
+    }
+
+    activate_account(account_id)?;
+    cancel_pending_switch()?;
+
+    Ok(ForcedSwitchReport {
+        account_id: account_id.to_string(),
+        switched: true,
+        processes,
+        error: None,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{signals, stop_processes};
+    use crate::types::{CodexProcessDetails, ProcessStopOutcome};
+    use std::process::Command;
+    use std::time::Duration;
+
+    fn details(pid: u32, uid: u32) -> CodexProcessDetails {
+        let mut process = CodexProcessDetails::from_pid(pid);
+        process.uid = Some(uid);
+        process
+    }
+
+    #[test]
+    fn terminates_own_processes_with_sigterm() {
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id();
+
+        // Reap the child as soon as it exits; only Linux can tell a zombie from a live process
+        let reaper = std::thread::spawn(move || child.wait());
+
+        let results = stop_processes(
+            &[details(pid, signals::current_uid())],
+            Duration::from_secs(5),
+            false,
+        );
+        let _ = reaper.join();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].outcome, ProcessStopOutcome::Terminated);
+    }
+
+    #[test]
+    fn refuses_processes_of_other_users() {
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+
+        let results = stop_processes(
+            &[details(child.id(), signals::current_uid().wrapping_add(1))],
+            Duration::from_secs(1),
+            true,
+        );
+        let still_running = signals::is_alive(child.id());
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert_eq!(results[0].outcome, ProcessStopOutcome::Refused);
+        assert!(still_running);
+    }
+}
@@ -1,6 +1,8 @@
 //! Core types for Codex Usage Inspector
 
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -13,6 +15,9 @@ pub struct AccountsStore {
     pub accounts: Vec<StoredAccount>,
     /// Currently active account ID
     pub active_account_id: Option<String>,
+    /// Deleted accounts that can still be restored
+    #[serde(default)]
+    pub trash: Vec<TrashedAccount>,
 }
 
 impl Default for AccountsStore {
@@ -21,10 +26,31 @@ impl Default for AccountsStore {
             version: 1,
             accounts: Vec::new(),
             active_account_id: None,
+            trash: Vec::new(),
         }
     }
 }
 
+/// A deleted account kept in the trash until it is restored or purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedAccount {
+    pub account: StoredAccount,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// A trashed account as shown to the frontend, without credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedAccountInfo {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub plan_type: Option<String>,
+    pub auth_mode: AuthMode,
+    pub deleted_at: DateTime<Utc>,
+    /// When the automatic purge will remove it, if enabled
+    pub purge_at: Option<DateTime<Utc>>,
+}
+
 /// A stored account with all its metadata and credentials
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredAccount {
@@ -44,6 +70,21 @@ pub struct StoredAccount {
     pub created_at: DateTime<Utc>,
     /// Last time this account was used
     pub last_used_at: Option<DateTime<Utc>>,
+    /// Organizations (workspaces) listed in the id token
+    #[serde(default)]
+    pub organizations: Vec<TokenOrganization>,
+    /// ChatGPT workspace written as `account_id` to auth.json instead of the token's own account ID
+    #[serde(default)]
+    pub selected_workspace_id: Option<String>,
+    /// User-defined tags such as `personal` or `team-a`, normalized and sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Free-form notes, e.g. "billing owner: X"
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Custom key/value fields such as cost center or owner
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl StoredAccount {
@@ -58,6 +99,11 @@ impl StoredAccount {
             auth_data: AuthData::ApiKey { key: api_key },
             created_at: Utc::now(),
             last_used_at: None,
+            organizations: Vec::new(),
+            selected_workspace_id: None,
+            tags: Vec::new(),
+            notes: None,
+            metadata: BTreeMap::new(),
         }
     }
 
@@ -71,7 +117,7 @@ impl StoredAccount {
         refresh_token: String,
         account_id: Option<String>,
     ) -> Self {
-        Self {
+        let mut account = Self {
             id: Uuid::new_v4().to_string(),
             name,
             email,
@@ -85,6 +131,52 @@ impl StoredAccount {
             },
             created_at: Utc::now(),
             last_used_at: None,
+            organizations: Vec::new(),
+            selected_workspace_id: None,
+            tags: Vec::new(),
+            notes: None,
+            metadata: BTreeMap::new(),
+        };
+        account.refresh_organizations();
+        account
+    }
+
+    /// Workspace IDs that may be written to auth.json: the token's account ID, then each
+    /// listed ChatGPT workspace.
+    ///
+    /// Platform organizations (`org-…`) are not ChatGPT accounts, so Codex cannot use them.
+    pub fn workspace_ids(&self) -> Vec<&str> {
+        let default_id = match &self.auth_data {
+            AuthData::ChatGPT { account_id, .. } => account_id.as_deref(),
+            AuthData::ApiKey { .. } => None,
+        };
+
+        default_id
+            .into_iter()
+            .chain(
+                self.organizations
+                    .iter()
+                    .map(|org| org.id.as_str())
+                    .filter(|id| !id.starts_with("org-")),
+            )
+            .collect()
+    }
+
+    /// Re-read the organization list from the id token.
+    ///
+    /// A selected workspace that the new token no longer lists is cleared.
+    pub fn refresh_organizations(&mut self) {
+        self.organizations = match &self.auth_data {
+            AuthData::ChatGPT { id_token, .. } => crate::auth::claims::parse_id_token(id_token)
+                .map(|claims| claims.organizations())
+                .unwrap_or_default(),
+            AuthData::ApiKey { .. } => Vec::new(),
+        };
+
+        if let Some(selected) = self.selected_workspace_id.as_deref() {
+            if !self.workspace_ids().contains(&selected) {
+                self.selected_workspace_id = None;
+            }
         }
     }
 }
@@ -154,6 +246,327 @@ pub struct TokenData {
     pub account_id: Option<String>,
 }
 
+// ============================================================================
+// Types for the switch journal (switch-journal.json)
+// ============================================================================
+
+/// Recent account switches, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwitchJournal {
+    pub entries: Vec<SwitchJournalEntry>,
+}
+
+/// One account switch and what it replaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchJournalEntry {
+    /// Unique identifier (UUID)
+    pub id: String,
+    /// When the switch happened
+    pub switched_at: DateTime<Utc>,
+    /// Active account ID before the switch
+    pub previous_active_account_id: Option<String>,
+    /// Account that was switched to
+    pub account_id: String,
+    /// What auth.json held before the switch
+    pub previous_auth: PreviousAuthState,
+}
+
+/// Content of auth.json before a switch overwrote it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreviousAuthState {
+    /// There was no auth.json
+    Missing,
+    /// auth.json held the credentials of a stored account
+    StoredAccount { account_id: String },
+    /// auth.json held a session not in the store, preserved as a snapshot file
+    Snapshot { path: String },
+}
+
+// ============================================================================
+// Types for token revocation (token-revocations.json)
+// ============================================================================
+
+/// Which token a revocation call targeted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevokedTokenType {
+    RefreshToken,
+    AccessToken,
+}
+
+/// Outcome of revoking one token at the issuer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRevocationResult {
+    pub token_type: RevokedTokenType,
+    pub revoked: bool,
+    pub error: Option<String>,
+}
+
+/// Revocation attempt for the tokens of a deleted account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRevocationReport {
+    pub account_id: String,
+    pub account_name: String,
+    pub attempted_at: DateTime<Utc>,
+    pub results: Vec<TokenRevocationResult>,
+}
+
+impl TokenRevocationReport {
+    /// Whether every token was revoked
+    pub fn all_revoked(&self) -> bool {
+        self.results.iter().all(|result| result.revoked)
+    }
+
+    /// Whether at least one token is no longer accepted by the issuer
+    pub fn any_revoked(&self) -> bool {
+        self.results.iter().any(|result| result.revoked)
+    }
+}
+
+/// Recorded revocation attempts, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenRevocationLog {
+    pub entries: Vec<TokenRevocationReport>,
+}
+
+/// Result of deleting an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeletionReport {
+    pub account_id: String,
+    /// Set when token revocation was requested
+    pub revocation: Option<TokenRevocationReport>,
+    /// Shown when the account was deleted locally but its tokens may still be valid
+    pub warning: Option<String>,
+}
+
+// ============================================================================
+// Types for activation history (activations.json)
+// ============================================================================
+
+/// Periods during which each account was the active one, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivationLog {
+    pub entries: Vec<ActivationRecord>,
+}
+
+/// One stretch of time an account spent as the active account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivationRecord {
+    pub account_id: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the account is still active
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// Time an account was active during one week (weeks start on Monday, UTC)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyActiveTime {
+    pub week_start: NaiveDate,
+    pub active_secs: i64,
+}
+
+/// Usage statistics for one stored account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountActivityStats {
+    pub account_id: String,
+    pub account_name: String,
+    pub is_active: bool,
+    /// Active time within the reporting window
+    pub active_secs: i64,
+    /// How many times the account was activated within the reporting window
+    pub activation_count: usize,
+    /// One entry per week of the reporting window, oldest first
+    pub weekly: Vec<WeeklyActiveTime>,
+    /// When the account was last active, falling back to `last_used_at`
+    pub last_active_at: Option<DateTime<Utc>>,
+    /// Whole days since the account was last active (or added, if never used)
+    pub days_unused: i64,
+}
+
+/// Switch history statistics across all stored accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountUsageStats {
+    pub generated_at: DateTime<Utc>,
+    /// Start of the reporting window
+    pub since: DateTime<Utc>,
+    pub weeks: u32,
+    pub unused_days: u32,
+    /// Activations within the reporting window
+    pub switch_count: usize,
+    pub switches_per_week: f64,
+    pub accounts: Vec<AccountActivityStats>,
+    /// Accounts not active for at least `unused_days`, least recently used first
+    pub unused_account_ids: Vec<String>,
+}
+
+// ============================================================================
+// Types for the audit log (audit-log.jsonl)
+// ============================================================================
+
+/// Kind of operation recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    AccountAdded,
+    CredentialsUpdated,
+    AccountsMerged,
+    AccountRenamed,
+    AccountReconnected,
+    AccountDeleted,
+    AccountRestored,
+    AccountPurged,
+    AccountSwitched,
+    SwitchUndone,
+    SnapshotCreated,
+}
+
+/// One line of the audit log. Never holds tokens or API keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// OS user the app was running as
+    pub actor: Option<String>,
+    pub action: AuditAction,
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    /// Free-form context such as the previous name or a snapshot file name
+    pub details: Option<String>,
+}
+
+/// Filters for querying or exporting the audit log; all are optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditLogQuery {
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time
+    pub until: Option<DateTime<Utc>>,
+    pub account_id: Option<String>,
+}
+
+// ============================================================================
+// Types for app settings (settings.json)
+// ============================================================================
+
+/// App-wide preferences stored next to accounts.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Retention rules for the session snapshots directory
+    pub snapshot_retention: SnapshotRetentionPolicy,
+    /// Patterns that identify running Codex processes
+    pub process_detection: ProcessDetectionSettings,
+    /// How long deleted accounts stay in the trash
+    pub trash: TrashSettings,
+}
+
+/// Automatic purge of deleted accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// Purge trashed accounts after this many days (1 to 3650); `None` keeps them until purged by hand
+    pub purge_after_days: Option<u32>,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            purge_after_days: Some(30),
+        }
+    }
+}
+
+/// Patterns used to recognize Codex processes.
+///
+/// A process counts as Codex when any pattern matches it. The defaults cover the
+/// native binary and the npm package's `codex.js` run by `node`, which is also what
+/// `npx @openai/codex` ends up starting. A bare `@openai/codex` argument is not
+/// matched, since `npm install` or `npm view` carry it too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessDetectionSettings {
+    pub patterns: Vec<ProcessMatchPattern>,
+}
+
+impl Default for ProcessDetectionSettings {
+    fn default() -> Self {
+        Self {
+            patterns: vec![
+                ProcessMatchPattern::new(ProcessMatchTarget::Name, "codex"),
+                ProcessMatchPattern::new(
+                    ProcessMatchTarget::Argument,
+                    "*/@openai/codex/bin/codex.js",
+                ),
+            ],
+        }
+    }
+}
+
+/// A glob pattern (`*` matches any run of characters) checked against one part of a process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessMatchPattern {
+    pub target: ProcessMatchTarget,
+    pub pattern: String,
+}
+
+impl ProcessMatchPattern {
+    pub fn new(target: ProcessMatchTarget, pattern: &str) -> Self {
+        Self {
+            target,
+            pattern: pattern.to_string(),
+        }
+    }
+}
+
+/// Which part of a process a pattern is checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessMatchTarget {
+    /// Executable name: `comm`, or the file name of the executable or `argv[0]`, without `.exe`
+    Name,
+    /// Full resolved executable path
+    Exe,
+    /// Any single command-line argument, including `argv[0]`
+    Argument,
+}
+
+/// The pattern that identified a process as Codex
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessPatternMatch {
+    pub pattern: ProcessMatchPattern,
+    /// The name, path or argument the pattern matched
+    pub matched_value: String,
+}
+
+/// Retention rules for session snapshots.
+///
+/// A snapshot survives pruning when any keep rule selects it. When no keep rule is
+/// set, every snapshot is kept by default. `max_age_days` is a hard limit applied
+/// after the keep rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotRetentionPolicy {
+    /// Keep the N most recent snapshots
+    pub keep_last: Option<u32>,
+    /// Keep the newest snapshot of each of the last N days that have snapshots
+    pub keep_daily: Option<u32>,
+    /// Keep the newest snapshot of each of the last N ISO weeks that have snapshots
+    pub keep_weekly: Option<u32>,
+    /// Delete snapshots older than this many days (at most 3650)
+    pub max_age_days: Option<u32>,
+}
+
+impl SnapshotRetentionPolicy {
+    /// Whether any of the keep-N rules are configured
+    pub fn has_keep_rules(&self) -> bool {
+        self.keep_last.is_some() || self.keep_daily.is_some() || self.keep_weekly.is_some()
+    }
+}
+
 // ============================================================================
 // Types for frontend communication
 // ============================================================================
@@ -169,10 +582,25 @@ pub struct AccountInfo {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub last_used_at: Option<DateTime<Utc>>,
+    /// Claims decoded from the stored tokens (ChatGPT accounts only)
+    pub token_claims: Option<AccountTokenClaims>,
+    /// Credential state derived from token expiry, without a network call
+    pub credential_health: CredentialHealth,
+    /// Organizations (workspaces) this login belongs to
+    pub organizations: Vec<TokenOrganization>,
+    /// Workspace written to auth.json instead of the token's own account ID
+    pub selected_workspace_id: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl AccountInfo {
     pub fn from_stored(account: &StoredAccount, active_id: Option<&str>) -> Self {
+        let token_claims = crate::auth::tokens::decode_account_token_claims(account);
+        let credential_health =
+            crate::auth::tokens::credential_health(account, token_claims.as_ref(), Utc::now());
+
         Self {
             id: account.id.clone(),
             name: account.name.clone(),
@@ -182,10 +610,115 @@ impl AccountInfo {
             is_active: active_id == Some(&account.id),
             created_at: account.created_at,
             last_used_at: account.last_used_at,
+            token_claims,
+            credential_health,
+            organizations: account.organizations.clone(),
+            selected_workspace_id: account.selected_workspace_id.clone(),
+            tags: account.tags.clone(),
+            notes: account.notes.clone(),
+            metadata: account.metadata.clone(),
         }
     }
 }
 
+/// What `query_accounts` sorts by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountSortKey {
+    /// The user's stored order
+    #[default]
+    Stored,
+    Name,
+    LastUsed,
+    CreatedAt,
+    /// Latest 5-hour window usage
+    PrimaryUsage,
+    /// Latest weekly window usage
+    SecondaryUsage,
+    PrimaryResetsAt,
+    SecondaryResetsAt,
+}
+
+/// Filters and ordering for `query_accounts`; every filter is optional.
+///
+/// Usage filters and usage sorts only see accounts whose usage was fetched since the
+/// app started; the cache is not persisted, so refresh usage first after a restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountQuery {
+    /// Case-insensitive text matched against name, email, plan, auth mode, tags, notes and metadata
+    pub text: Option<String>,
+    pub plan_type: Option<String>,
+    pub auth_mode: Option<AuthMode>,
+    pub is_active: Option<bool>,
+    pub tag: Option<String>,
+    /// Only accounts last used at or after this time
+    pub last_used_after: Option<DateTime<Utc>>,
+    /// Only accounts last used before this time, or never used
+    pub last_used_before: Option<DateTime<Utc>>,
+    /// Usage thresholds in percent, compared with the latest usage fetched since startup.
+    /// Accounts without fetched usage do not match a threshold.
+    pub min_primary_used_percent: Option<f64>,
+    pub max_primary_used_percent: Option<f64>,
+    pub min_secondary_used_percent: Option<f64>,
+    pub max_secondary_used_percent: Option<f64>,
+    pub sort_by: AccountSortKey,
+    pub descending: bool,
+}
+
+/// One account matched by `query_accounts` with its latest fetched usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountQueryMatch {
+    pub account: AccountInfo,
+    pub usage: Option<UsageInfo>,
+}
+
+/// A tag and how many accounts carry it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountTagSummary {
+    pub tag: String,
+    pub account_count: usize,
+}
+
+/// Claims decoded from a stored account's id and access tokens
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountTokenClaims {
+    /// `sub` of the id token
+    pub subject: Option<String>,
+    pub plan_type: Option<String>,
+    pub organizations: Vec<TokenOrganization>,
+    pub id_token_issued_at: Option<DateTime<Utc>>,
+    pub id_token_expires_at: Option<DateTime<Utc>>,
+    pub access_token_issued_at: Option<DateTime<Utc>>,
+    pub access_token_expires_at: Option<DateTime<Utc>>,
+}
+
+/// A ChatGPT organization listed in the id token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenOrganization {
+    pub id: String,
+    pub title: Option<String>,
+    pub role: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+/// Credential state of a stored account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialHealth {
+    /// Access token valid for a while yet (API keys are always valid)
+    Valid,
+    /// Access token expires within a day
+    ExpiringSoon,
+    /// Access token expired but a refresh token can renew it
+    RefreshRequired,
+    /// Access token expired and the refresh token is missing or has gone stale
+    Expired,
+    /// Token expiry could not be read
+    Unknown,
+}
+
 /// Usage information for an account
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageInfo {
@@ -237,10 +770,145 @@ impl UsageInfo {
 /// OAuth login information returned to frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthLoginInfo {
+    /// Identifies this login among concurrent ones
+    pub login_id: String,
     /// The authorization URL to open in browser
     pub auth_url: String,
     /// The local callback port
     pub callback_port: u16,
+    /// Where the browser is sent after sign-in; manual logins paste this URL back
+    pub redirect_uri: String,
+}
+
+/// Phase of an OAuth login
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthLoginPhase {
+    /// Waiting for the browser to come back with a callback or pasted URL
+    WaitingForBrowser,
+    CallbackReceived,
+    StateVerified,
+    ExchangingTokens,
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+impl OAuthLoginPhase {
+    /// Whether the login has ended
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            Self::Succeeded | Self::Failed | Self::Cancelled | Self::TimedOut
+        )
+    }
+}
+
+/// A phase change reported while a login runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthLoginProgress {
+    pub login_id: String,
+    pub phase: OAuthLoginPhase,
+    pub reason: Option<String>,
+}
+
+/// Status of an OAuth login, emitted on every phase change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthLoginStatus {
+    pub login_id: String,
+    pub account_name: String,
+    /// Set when the login replaces the credentials of an existing account
+    pub reconnect_account_id: Option<String>,
+    /// Finished by pasting the redirect URL rather than through a local callback
+    pub manual: bool,
+    pub callback_port: u16,
+    pub phase: OAuthLoginPhase,
+    /// Why the login failed, or why a callback was ignored
+    pub reason: Option<String>,
+    /// Whether the browser was opened for this login
+    pub browser_opened: bool,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// When the login times out
+    pub expires_at: DateTime<Utc>,
+    /// Seconds left before the timeout; `None` once the login has ended
+    pub remaining_secs: Option<u64>,
+}
+
+/// A running Codex CLI process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexProcessDetails {
+    pub pid: u32,
+    /// Short process name (`comm` on Linux)
+    pub name: Option<String>,
+    /// Full argument vector
+    pub cmdline: Vec<String>,
+    /// Resolved executable path
+    pub exe: Option<String>,
+    /// Working directory
+    pub cwd: Option<String>,
+    /// Real user ID of the process owner
+    pub uid: Option<u32>,
+    pub started_at: Option<DateTime<Utc>>,
+    /// Where the details came from
+    pub source: ProcessInfoSource,
+    /// Detection pattern that matched this process
+    pub matched: Option<ProcessPatternMatch>,
+    /// Codex home the process reads auth.json from, when it could be determined
+    pub codex_home: Option<String>,
+    pub codex_home_source: CodexHomeSource,
+    /// Stored account whose credentials are in that home's auth.json
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    /// Whether the process uses the Codex home a switch writes to.
+    /// Processes with an unknown home are assumed to share it.
+    pub blocks_switch: bool,
+}
+
+impl CodexProcessDetails {
+    /// Details for a process known only by PID
+    pub fn from_pid(pid: u32) -> Self {
+        Self {
+            pid,
+            name: None,
+            cmdline: Vec::new(),
+            exe: None,
+            cwd: None,
+            uid: None,
+            started_at: None,
+            source: ProcessInfoSource::ProcessList,
+            matched: None,
+            codex_home: None,
+            codex_home_source: CodexHomeSource::Unknown,
+            account_id: None,
+            account_name: None,
+            blocks_switch: true,
+        }
+    }
+}
+
+/// How process details were collected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessInfoSource {
+    /// Read directly from `/proc/<pid>`
+    Procfs,
+    /// Parsed from `ps -eo pid=,uid=,args=` (PID, uid and arguments) or, on Windows,
+    /// `tasklist` (PID and image name only); no executable path, working directory or start time
+    ProcessList,
+}
+
+/// How the Codex home of a running process was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodexHomeSource {
+    /// `CODEX_HOME` is set in the process environment
+    Environment,
+    /// `CODEX_HOME` is unset, so Codex uses `$HOME/.codex`
+    Default,
+    /// The process environment could not be read
+    Unknown,
 }
 
 /// Current Codex auth.json session metadata (no token values)
@@ -256,6 +924,209 @@ pub struct CurrentAuthSummary {
     pub message: Option<String>,
 }
 
+/// A session snapshot file (metadata only, never the file content)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFileInfo {
+    pub file_name: String,
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Result of applying the snapshot retention policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotPruneReport {
+    /// True when nothing was deleted and the report is only a preview
+    pub dry_run: bool,
+    pub policy: SnapshotRetentionPolicy,
+    pub kept: Vec<SnapshotFileInfo>,
+    pub pruned: Vec<SnapshotFileInfo>,
+}
+
+/// Result of undoing the last account switch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchUndoResult {
+    /// The journal entry that was undone
+    pub undone: SwitchJournalEntry,
+    /// Active account ID after the undo
+    pub active_account_id: Option<String>,
+    /// What auth.json held right before the undo, if it was not a stored account
+    pub replaced_auth: PreviousAuthState,
+}
+
+/// A switch waiting for the Codex sessions that share the Codex home to exit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingSwitch {
+    pub id: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub queued_at: DateTime<Utc>,
+    /// PIDs still blocking the switch at the last check
+    pub waiting_for_pids: Vec<u32>,
+}
+
+/// How a pending switch was resolved, sent with the pending switch events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSwitchOutcome {
+    pub pending: PendingSwitch,
+    pub applied: bool,
+    /// Why the switch could not be applied
+    pub error: Option<String>,
+    pub resolved_at: DateTime<Utc>,
+}
+
+/// What happened to one process when stopping Codex before a forced switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessStopOutcome {
+    /// Exited after SIGTERM
+    Terminated,
+    /// Exited after SIGKILL
+    Killed,
+    /// Was already gone before it could be signalled
+    AlreadyExited,
+    /// Still running after the timeout
+    StillRunning,
+    /// Not signalled because another user owns it
+    Refused,
+    /// Signalling failed
+    Failed,
+}
+
+/// Per-process result of stopping Codex
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessStopResult {
+    pub pid: u32,
+    pub name: Option<String>,
+    pub outcome: ProcessStopOutcome,
+    pub message: Option<String>,
+}
+
+/// Result of stopping Codex processes and then switching accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForcedSwitchReport {
+    pub account_id: String,
+    /// Whether auth.json was switched; false when a process could not be stopped
+    pub switched: bool,
+    pub processes: Vec<ProcessStopResult>,
+    /// Why the switch was not performed
+    pub error: Option<String>,
+}
+
+/// What to do when an imported file holds a login that is already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateStrategy {
+    /// Leave the stored account untouched
+    #[default]
+    Skip,
+    /// Update the stored account's credentials from the file
+    Merge,
+}
+
+/// Which identity rule recognized two records as the same login
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityMatch {
+    ChatgptAccountId,
+    EmailAndPlan,
+    ApiKey,
+}
+
+/// Stored records that hold the same login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateAccountGroup {
+    pub matched_by: IdentityMatch,
+    pub accounts: Vec<AccountInfo>,
+}
+
+/// Outcome of importing a single file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkImportStatus {
+    Imported,
+    Skipped,
+    Merged,
+    Failed,
+}
+
+/// Per-file entry of a bulk import report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkImportFileResult {
+    pub file_name: String,
+    pub path: String,
+    pub status: BulkImportStatus,
+    /// Account that was created, merged into, or matched as a duplicate
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Result of importing every file in a directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkImportReport {
+    pub directory: String,
+    pub imported: usize,
+    pub skipped: usize,
+    pub merged: usize,
+    pub failed: usize,
+    pub files: Vec<BulkImportFileResult>,
+}
+
+impl BulkImportReport {
+    pub fn new(directory: String, files: Vec<BulkImportFileResult>) -> Self {
+        let count = |status: BulkImportStatus| files.iter().filter(|f| f.status == status).count();
+        Self {
+            directory,
+            imported: count(BulkImportStatus::Imported),
+            skipped: count(BulkImportStatus::Skipped),
+            merged: count(BulkImportStatus::Merged),
+            failed: count(BulkImportStatus::Failed),
+            files,
+        }
+    }
+}
+
+/// Result of writing an encrypted account bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleExportResult {
+    pub path: String,
+    pub account_count: usize,
+}
+
+/// What importing a bundled account does to the local store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleImportAction {
+    /// Added as a new account
+    Add,
+    /// Credentials copied into the local account holding the same login
+    Update,
+    /// Left out because the login is already stored
+    Skip,
+}
+
+/// One bundled account in an import preview or result (no credentials)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportEntry {
+    /// Name the account will have locally
+    pub name: String,
+    pub email: Option<String>,
+    pub plan_type: Option<String>,
+    pub auth_mode: AuthMode,
+    pub action: BundleImportAction,
+    pub existing_account_id: Option<String>,
+    pub existing_account_name: Option<String>,
+}
+
+/// Preview or result of importing an account bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportPlan {
+    pub exported_at: DateTime<Utc>,
+    /// False for a preview, true once the store was updated
+    pub applied: bool,
+    pub entries: Vec<BundleImportEntry>,
+}
+
 /// Current auth summary status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -18,6 +18,13 @@ const WORK_ACCOUNT = {
   is_active: true,
   created_at: new Date().toISOString(),
   last_used_at: null,
+  token_claims: null,
+  credential_health: "valid",
+  organizations: [],
+  selected_workspace_id: null,
+  tags: [],
+  notes: null,
+  metadata: {},
 };
 
 const PERSONAL_ACCOUNT = {
@@ -29,6 +36,13 @@ const PERSONAL_ACCOUNT = {
   is_active: false,
   created_at: new Date().toISOString(),
   last_used_at: null,
+  token_claims: null,
+  credential_health: "valid",
+  organizations: [],
+  selected_workspace_id: null,
+  tags: [],
+  notes: null,
+  metadata: {},
 };
 
 vi.mock("./hooks/useAccounts", () => ({
@@ -45,6 +59,7 @@ beforeEach(() => {
         count: 0,
         can_switch: true,
         pids: [],
+        processes: [],
       };
     }
 
@@ -147,6 +162,13 @@ it("clears delete confirmation message after timeout", async () => {
           is_active: true,
           created_at: new Date().toISOString(),
           last_used_at: null,
+          token_claims: null,
+          credential_health: "valid",
+          organizations: [],
+          selected_workspace_id: null,
+          tags: [],
+          notes: null,
+          metadata: {},
         },
       ],
     })
@@ -296,6 +318,7 @@ it("keeps recent activity and blocking pid status visible", async () => {
         count: 2,
         can_switch: false,
         pids: [111, 222],
+        processes: [],
       };
     }
 
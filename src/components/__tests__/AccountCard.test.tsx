@@ -16,6 +16,13 @@ it("uses the same controls layout for active and inactive cards without switch C
           is_active: true,
           created_at: new Date().toISOString(),
           last_used_at: null,
+          token_claims: null,
+          credential_health: "valid",
+          organizations: [],
+          selected_workspace_id: null,
+          tags: [],
+          notes: null,
+          metadata: {},
         }}
         onDelete={() => {}}
         onRefresh={async () => {}}
@@ -33,6 +40,13 @@ it("uses the same controls layout for active and inactive cards without switch C
           is_active: false,
           created_at: new Date().toISOString(),
           last_used_at: null,
+          token_claims: null,
+          credential_health: "valid",
+          organizations: [],
+          selected_workspace_id: null,
+          tags: [],
+          notes: null,
+          metadata: {},
         }}
         onDelete={() => {}}
         onRefresh={async () => {}}
@@ -70,6 +84,13 @@ it("shows credits beside account identity when credits balance exists", () => {
         is_active: false,
         created_at: new Date().toISOString(),
         last_used_at: null,
+        token_claims: null,
+        credential_health: "valid",
+        organizations: [],
+        selected_workspace_id: null,
+        tags: [],
+        notes: null,
+        metadata: {},
         usage: {
           account_id: "acc-credits",
           plan_type: "plus",
@@ -106,6 +127,13 @@ it("hides reconnect action for api key accounts", () => {
         is_active: false,
         created_at: new Date().toISOString(),
         last_used_at: null,
+        token_claims: null,
+        credential_health: "valid",
+        organizations: [],
+        selected_workspace_id: null,
+        tags: [],
+        notes: null,
+        metadata: {},
       }}
       onDelete={() => {}}
       onRefresh={async () => {}}
@@ -129,6 +157,13 @@ it("renders compact mode with inline refresh icon and no footer refresh button",
         is_active: false,
         created_at: new Date().toISOString(),
         last_used_at: null,
+        token_claims: null,
+        credential_health: "valid",
+        organizations: [],
+        selected_workspace_id: null,
+        tags: [],
+        notes: null,
+        metadata: {},
         usage: {
           account_id: "acc-compact",
           plan_type: "plus",
@@ -17,6 +17,13 @@ function createAccount(overrides: Partial<AccountWithUsage>): AccountWithUsage {
     is_active: false,
     created_at: "2026-01-01T00:00:00.000Z",
     last_used_at: null,
+    token_claims: null,
+    credential_health: "valid",
+    organizations: [],
+    selected_workspace_id: null,
+    tags: [],
+    notes: null,
+    metadata: {},
     ...overrides,
   };
 }
@@ -17,6 +17,7 @@ const processInfo: CodexProcessInfo = {
   count: 2,
   can_switch: false,
   pids: [1234, 5678],
+  processes: [],
 };
 
 function createProps(overrides?: Partial<Parameters<typeof WorkbenchHeader>[0]>) {
@@ -14,6 +14,13 @@ const OAUTH_ACCOUNT: AccountInfo = {
   is_active: true,
   created_at: new Date().toISOString(),
   last_used_at: null,
+  token_claims: null,
+  credential_health: "valid",
+  organizations: [],
+  selected_workspace_id: null,
+  tags: [],
+  notes: null,
+  metadata: {},
 };
 
 const SECOND_ACCOUNT: AccountInfo = {
@@ -25,6 +32,13 @@ const SECOND_ACCOUNT: AccountInfo = {
   is_active: false,
   created_at: new Date().toISOString(),
   last_used_at: null,
+  token_claims: null,
+  credential_health: "valid",
+  organizations: [],
+  selected_workspace_id: null,
+  tags: [],
+  notes: null,
+  metadata: {},
 };
 
 beforeEach(() => {
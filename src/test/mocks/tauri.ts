@@ -4,12 +4,14 @@ type CodexProcessInfo = {
   count: number;
   can_switch: boolean;
   pids: number[];
+  processes: unknown[];
 };
 
 const defaultProcessInfo: CodexProcessInfo = {
   count: 0,
   can_switch: true,
   pids: [],
+  processes: [],
 };
 
 export const invokeMock = vi.fn(async (command: string) => {
@@ -75,6 +75,13 @@ export function createUseAccountsMock(
       is_active: false,
       created_at: new Date().toISOString(),
       last_used_at: null,
+      token_claims: null,
+      credential_health: "valid",
+      organizations: [],
+      selected_workspace_id: null,
+      tags: [],
+      notes: null,
+      metadata: {},
     })),
     cancelOAuthLogin: vi.fn(asyncNoop),
     reconnectAccount: vi.fn(async (_accountId: string): Promise<AccountInfo> => ({
@@ -86,6 +93,13 @@ export function createUseAccountsMock(
       is_active: true,
       created_at: new Date().toISOString(),
       last_used_at: null,
+      token_claims: null,
+      credential_health: "valid",
+      organizations: [],
+      selected_workspace_id: null,
+      tags: [],
+      notes: null,
+      metadata: {},
     })),
     refreshCurrentSession: vi.fn(async (): Promise<CurrentAuthSummary> => ({
       status: "missing",
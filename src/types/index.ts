@@ -11,6 +11,37 @@ export interface AccountInfo {
   is_active: boolean;
   created_at: string;
   last_used_at: string | null;
+  token_claims: AccountTokenClaims | null;
+  credential_health: CredentialHealth;
+  organizations: TokenOrganization[];
+  selected_workspace_id: string | null;
+  tags: string[];
+  notes: string | null;
+  metadata: Record<string, string>;
+}
+
+export type CredentialHealth =
+  | "valid"
+  | "expiring_soon"
+  | "refresh_required"
+  | "expired"
+  | "unknown";
+
+export interface TokenOrganization {
+  id: string;
+  title: string | null;
+  role: string | null;
+  is_default: boolean;
+}
+
+export interface AccountTokenClaims {
+  subject: string | null;
+  plan_type: string | null;
+  organizations: TokenOrganization[];
+  id_token_issued_at: string | null;
+  id_token_expires_at: string | null;
+  access_token_issued_at: string | null;
+  access_token_expires_at: string | null;
 }
 
 export interface UsageInfo {
@@ -29,8 +60,35 @@ export interface UsageInfo {
 }
 
 export interface OAuthLoginInfo {
+  login_id: string;
   auth_url: string;
   callback_port: number;
+  redirect_uri: string;
+}
+
+export type OAuthLoginPhase =
+  | "waiting_for_browser"
+  | "callback_received"
+  | "state_verified"
+  | "exchanging_tokens"
+  | "succeeded"
+  | "failed"
+  | "cancelled"
+  | "timed_out";
+
+export interface OAuthLoginStatus {
+  login_id: string;
+  account_name: string;
+  reconnect_account_id: string | null;
+  manual: boolean;
+  callback_port: number;
+  phase: OAuthLoginPhase;
+  reason: string | null;
+  browser_opened: boolean;
+  started_at: string;
+  updated_at: string;
+  expires_at: string;
+  remaining_secs: number | null;
 }
 
 export interface AccountWithUsage extends AccountInfo {
@@ -42,6 +100,44 @@ export interface CodexProcessInfo {
   count: number;
   can_switch: boolean;
   pids: number[];
+  processes: CodexProcessDetails[];
+}
+
+export type ProcessInfoSource = "procfs" | "process_list";
+
+export interface CodexProcessDetails {
+  pid: number;
+  name: string | null;
+  cmdline: string[];
+  exe: string | null;
+  cwd: string | null;
+  uid: number | null;
+  started_at: string | null;
+  source: ProcessInfoSource;
+  matched: ProcessPatternMatch | null;
+  codex_home: string | null;
+  codex_home_source: CodexHomeSource;
+  account_id: string | null;
+  account_name: string | null;
+  blocks_switch: boolean;
+}
+
+export type CodexHomeSource = "environment" | "default" | "unknown";
+
+export type ProcessMatchTarget = "name" | "exe" | "argument";
+
+export interface ProcessMatchPattern {
+  target: ProcessMatchTarget;
+  pattern: string;
+}
+
+export interface ProcessPatternMatch {
+  pattern: ProcessMatchPattern;
+  matched_value: string;
+}
+
+export interface ProcessDetectionSettings {
+  patterns: ProcessMatchPattern[];
 }
 
 export type CurrentAuthStatus = "ready" | "missing" | "invalid" | "error";
@@ -56,3 +152,263 @@ export interface CurrentAuthSummary {
   last_modified_at: string | null;
   message: string | null;
 }
+
+export interface SnapshotRetentionPolicy {
+  keep_last: number | null;
+  keep_daily: number | null;
+  keep_weekly: number | null;
+  max_age_days: number | null;
+}
+
+export interface SnapshotFileInfo {
+  file_name: string;
+  path: string;
+  created_at: string;
+}
+
+export interface SnapshotPruneReport {
+  dry_run: boolean;
+  policy: SnapshotRetentionPolicy;
+  kept: SnapshotFileInfo[];
+  pruned: SnapshotFileInfo[];
+}
+
+export type PreviousAuthState =
+  | { kind: "missing" }
+  | { kind: "stored_account"; account_id: string }
+  | { kind: "snapshot"; path: string };
+
+export interface SwitchJournalEntry {
+  id: string;
+  switched_at: string;
+  previous_active_account_id: string | null;
+  account_id: string;
+  previous_auth: PreviousAuthState;
+}
+
+export interface SwitchUndoResult {
+  undone: SwitchJournalEntry;
+  active_account_id: string | null;
+  replaced_auth: PreviousAuthState;
+}
+
+export type RevokedTokenType = "refresh_token" | "access_token";
+
+export interface TokenRevocationResult {
+  token_type: RevokedTokenType;
+  revoked: boolean;
+  error: string | null;
+}
+
+export interface TokenRevocationReport {
+  account_id: string;
+  account_name: string;
+  attempted_at: string;
+  results: TokenRevocationResult[];
+}
+
+export interface AccountDeletionReport {
+  account_id: string;
+  revocation: TokenRevocationReport | null;
+  warning: string | null;
+}
+
+export interface TrashedAccountInfo {
+  id: string;
+  name: string;
+  email: string | null;
+  plan_type: string | null;
+  auth_mode: AuthMode;
+  deleted_at: string;
+  purge_at: string | null;
+}
+
+export interface TrashSettings {
+  purge_after_days: number | null;
+}
+
+export type AccountSortKey =
+  | "stored"
+  | "name"
+  | "last_used"
+  | "created_at"
+  | "primary_usage"
+  | "secondary_usage"
+  | "primary_resets_at"
+  | "secondary_resets_at";
+
+export interface AccountQuery {
+  text?: string | null;
+  plan_type?: string | null;
+  auth_mode?: AuthMode | null;
+  is_active?: boolean | null;
+  tag?: string | null;
+  last_used_after?: string | null;
+  last_used_before?: string | null;
+  min_primary_used_percent?: number | null;
+  max_primary_used_percent?: number | null;
+  min_secondary_used_percent?: number | null;
+  max_secondary_used_percent?: number | null;
+  sort_by?: AccountSortKey;
+  descending?: boolean;
+}
+
+export interface AccountQueryMatch {
+  account: AccountInfo;
+  usage: UsageInfo | null;
+}
+
+export interface AccountTagSummary {
+  tag: string;
+  account_count: number;
+}
+
+export interface ActivationRecord {
+  account_id: string;
+  started_at: string;
+  ended_at: string | null;
+}
+
+export interface WeeklyActiveTime {
+  week_start: string;
+  active_secs: number;
+}
+
+export interface AccountActivityStats {
+  account_id: string;
+  account_name: string;
+  is_active: boolean;
+  active_secs: number;
+  activation_count: number;
+  weekly: WeeklyActiveTime[];
+  last_active_at: string | null;
+  days_unused: number;
+}
+
+export interface AccountUsageStats {
+  generated_at: string;
+  since: string;
+  weeks: number;
+  unused_days: number;
+  switch_count: number;
+  switches_per_week: number;
+  accounts: AccountActivityStats[];
+  unused_account_ids: string[];
+}
+
+export type AuditAction =
+  | "account_added"
+  | "credentials_updated"
+  | "accounts_merged"
+  | "account_renamed"
+  | "account_reconnected"
+  | "account_deleted"
+  | "account_restored"
+  | "account_purged"
+  | "account_switched"
+  | "switch_undone"
+  | "snapshot_created";
+
+export interface AuditEntry {
+  timestamp: string;
+  actor: string | null;
+  action: AuditAction;
+  account_id: string | null;
+  account_name: string | null;
+  details: string | null;
+}
+
+export interface AuditLogQuery {
+  since?: string | null;
+  until?: string | null;
+  account_id?: string | null;
+}
+
+export type DuplicateStrategy = "skip" | "merge";
+
+export type BulkImportStatus = "imported" | "skipped" | "merged" | "failed";
+
+export interface BulkImportFileResult {
+  file_name: string;
+  path: string;
+  status: BulkImportStatus;
+  account_id: string | null;
+  account_name: string | null;
+  message: string | null;
+}
+
+export interface BulkImportReport {
+  directory: string;
+  imported: number;
+  skipped: number;
+  merged: number;
+  failed: number;
+  files: BulkImportFileResult[];
+}
+
+export type IdentityMatch = "chatgpt_account_id" | "email_and_plan" | "api_key";
+
+export interface DuplicateAccountGroup {
+  matched_by: IdentityMatch;
+  accounts: AccountInfo[];
+}
+
+export interface BundleExportResult {
+  path: string;
+  account_count: number;
+}
+
+export type BundleImportAction = "add" | "update" | "skip";
+
+export interface BundleImportEntry {
+  name: string;
+  email: string | null;
+  plan_type: string | null;
+  auth_mode: AuthMode;
+  action: BundleImportAction;
+  existing_account_id: string | null;
+  existing_account_name: string | null;
+}
+
+export interface BundleImportPlan {
+  exported_at: string;
+  applied: boolean;
+  entries: BundleImportEntry[];
+}
+
+export interface PendingSwitch {
+  id: string;
+  account_id: string;
+  account_name: string;
+  queued_at: string;
+  waiting_for_pids: number[];
+}
+
+export interface PendingSwitchOutcome {
+  pending: PendingSwitch;
+  applied: boolean;
+  error: string | null;
+  resolved_at: string;
+}
+
+export type ProcessStopOutcome =
+  | "terminated"
+  | "killed"
+  | "already_exited"
+  | "still_running"
+  | "refused"
+  | "failed";
+
+export interface ProcessStopResult {
+  pid: number;
+  name: string | null;
+  outcome: ProcessStopOutcome;
+  message: string | null;
+}
+
+export interface ForcedSwitchReport {
+  account_id: string;
+  switched: boolean;
+  processes: ProcessStopResult[];
+  error: string | null;
+}